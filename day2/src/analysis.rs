@@ -0,0 +1,108 @@
+use std::cmp::Reverse;
+
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::{
+    guide::Guide,
+    rules::{GameRules, Shape},
+    symbols::{ParseError, SymbolMap},
+    Score,
+};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AnalysisError {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("guide uses {found} response symbols but the rules only have {shapes} shapes")]
+    TooManyResponses { found: usize, shapes: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interpretation {
+    pub assignment: Vec<(String, Shape)>,
+    pub total: Score,
+}
+
+impl Interpretation {
+    pub fn describe(&self, rules: &GameRules) -> String {
+        self.assignment
+            .iter()
+            .map(|(symbol, shape)| format!("{}={}", symbol, rules.name(*shape)))
+            .join(" ")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    pub best: Score,
+    pub worst: Score,
+    pub interpretations: Vec<Interpretation>,
+}
+
+impl Analysis {
+    /// Scores the guide under every assignment of distinct shapes to its
+    /// response symbols. Only the opponent column is decoded through `symbols`.
+    pub fn new(
+        rules: &GameRules,
+        symbols: &SymbolMap,
+        guide: &Guide,
+    ) -> Result<Self, AnalysisError> {
+        let opponents = guide.interpret(symbols, |_| Some(()))?;
+        let scores = |theirs: Shape| rules.shapes().map(move |ours| rules.score(theirs, ours));
+        let best = opponents
+            .iter()
+            .map(|(theirs, _)| scores(*theirs).max().unwrap_or_default())
+            .sum();
+        let worst = opponents
+            .iter()
+            .map(|(theirs, _)| scores(*theirs).min().unwrap_or_default())
+            .sum();
+
+        let responses = guide.responses();
+        let shapes = rules.shapes().count();
+        if responses.len() > shapes {
+            return Err(AnalysisError::TooManyResponses {
+                found: responses.len(),
+                shapes,
+            });
+        }
+        let interpretations = rules
+            .shapes()
+            .permutations(responses.len())
+            .map(|shapes| {
+                let rounds = guide.interpret(symbols, |symbol| {
+                    let index = responses.iter().position(|r| *r == symbol)?;
+                    Some(shapes[index])
+                })?;
+                Ok(Interpretation {
+                    assignment: responses
+                        .iter()
+                        .map(|r| r.to_string())
+                        .zip(shapes)
+                        .collect(),
+                    total: rounds
+                        .into_iter()
+                        .map(|(theirs, ours)| rules.score(theirs, ours))
+                        .sum(),
+                })
+            })
+            .collect::<Result<_, ParseError>>()?;
+        Ok(Self {
+            best,
+            worst,
+            interpretations,
+        })
+    }
+
+    /// The interpretation under which the guide scores the most, preferring
+    /// the earliest listed on ties. This is only a heuristic for what the
+    /// author meant: the guide was given to us as a way to win, so the reading
+    /// that wins the most is the best guess among the shape readings. The
+    /// outcome reading of the column is not considered.
+    pub fn best_interpretation(&self) -> Option<&Interpretation> {
+        self.interpretations
+            .iter()
+            .min_by_key(|interpretation| Reverse(interpretation.total))
+    }
+}
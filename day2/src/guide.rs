@@ -0,0 +1,105 @@
+use itertools::Itertools;
+
+use crate::{rules::Shape, symbols::ParseError, symbols::SymbolMap, Round};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub line: usize,
+    pub opponent: String,
+    pub response: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShapeRound {
+    pub theirs: Shape,
+    pub ours: Shape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutcomeRound {
+    pub theirs: Shape,
+    pub outcome: Round,
+}
+
+/// A strategy guide as written, with both columns kept as raw symbols until
+/// an interpretation decides what the second column means.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Guide {
+    pub entries: Vec<Entry>,
+}
+
+impl Guide {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let entries = input
+            .lines()
+            .enumerate()
+            .map(|(index, text)| {
+                let line = index + 1;
+                let (opponent, response) =
+                    text.split(' ')
+                        .collect_tuple()
+                        .ok_or_else(|| ParseError::Malformed {
+                            line,
+                            text: text.to_owned(),
+                        })?;
+                Ok(Entry {
+                    line,
+                    opponent: opponent.to_owned(),
+                    response: response.to_owned(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { entries })
+    }
+
+    /// Decodes the opponent column through `symbols` and the response column
+    /// through `response`; `as_shapes` and `as_outcomes` are built on this.
+    pub fn interpret<T>(
+        &self,
+        symbols: &SymbolMap,
+        response: impl Fn(&str) -> Option<T>,
+    ) -> Result<Vec<(Shape, T)>, ParseError> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let theirs = symbols.opponent(&entry.opponent).ok_or_else(|| {
+                    ParseError::UnknownOpponent {
+                        line: entry.line,
+                        symbol: entry.opponent.clone(),
+                    }
+                })?;
+                let ours =
+                    response(&entry.response).ok_or_else(|| ParseError::UnknownResponse {
+                        line: entry.line,
+                        symbol: entry.response.clone(),
+                    })?;
+                Ok((theirs, ours))
+            })
+            .collect()
+    }
+
+    pub fn as_shapes(&self, symbols: &SymbolMap) -> Result<Vec<ShapeRound>, ParseError> {
+        let rounds = self.interpret(symbols, |symbol| symbols.shape(symbol))?;
+        Ok(rounds
+            .into_iter()
+            .map(|(theirs, ours)| ShapeRound { theirs, ours })
+            .collect())
+    }
+
+    pub fn as_outcomes(&self, symbols: &SymbolMap) -> Result<Vec<OutcomeRound>, ParseError> {
+        let rounds = self.interpret(symbols, |symbol| symbols.outcome(symbol))?;
+        Ok(rounds
+            .into_iter()
+            .map(|(theirs, outcome)| OutcomeRound { theirs, outcome })
+            .collect())
+    }
+
+    pub fn responses(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .map(|entry| entry.response.as_str())
+            .unique()
+            .sorted()
+            .collect()
+    }
+}
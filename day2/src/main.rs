@@ -1,48 +1,23 @@
-use std::{char::ParseCharError, str::FromStr};
+mod analysis;
+mod guide;
+mod rules;
+mod symbols;
+mod transcript;
+
+use std::str::FromStr;
 
 use anyhow::anyhow;
-use itertools::Itertools;
 
-type Input = [ShapeTuple];
+use analysis::Analysis;
+use guide::{Guide, OutcomeRound, ShapeRound};
+use rules::GameRules;
+use symbols::SymbolMap;
+use transcript::Transcript;
+
+type Input = Guide;
 type Score = u32;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Shape {
-    Rock = 1,
-    Paper = 2,
-    Scissor = 3,
-}
-
-impl FromStr for Shape {
-    type Err = ParseCharError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let shape = match s {
-            "A" | "X" => Self::Rock,
-            "B" | "Y" => Self::Paper,
-            "C" | "Z" => Self::Scissor,
-            _ => todo!(),
-        };
-        Ok(shape)
-    }
-}
-
-type ShapeTuple = (Shape, Shape, Round);
-
-fn parse_shape_tuple(input: &str) -> anyhow::Result<ShapeTuple> {
-    let (theirs, ours) = input
-        .split(' ')
-        .collect_tuple()
-        .ok_or(anyhow!("Failed to collect"))?;
-    let parsed_tuple = (
-        Shape::from_str(theirs)?,
-        Shape::from_str(ours)?,
-        Round::from_str(ours)?,
-    );
-    Ok(parsed_tuple)
-}
-
-#[derive(Debug, Clone, Copy)]
 enum Round {
     Lose = 0,
     Draw = 3,
@@ -50,88 +25,338 @@ enum Round {
 }
 
 impl FromStr for Round {
-    type Err = ParseCharError;
+    type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let shape = match s {
-            "X" => Self::Lose,
-            "Y" => Self::Draw,
-            "Z" => Self::Win,
-            _ => todo!(),
+        let round = match s {
+            "Lose" => Self::Lose,
+            "Draw" => Self::Draw,
+            "Win" => Self::Win,
+            _ => return Err(anyhow!("Unknown outcome {s:?}")),
         };
-        Ok(shape)
+        Ok(round)
     }
 }
 
-fn parse(input: &str) -> anyhow::Result<Vec<ShapeTuple>> {
-    input.lines().map(parse_shape_tuple).collect()
+fn parse(input: &str) -> anyhow::Result<Input> {
+    Ok(Guide::parse(input)?)
 }
 
-fn calculate_score(shapes: &ShapeTuple) -> Score {
-    let (theirs, ours, _) = shapes;
-    if theirs == ours {
-        return Round::Draw as u32 + *ours as u32;
-    }
-
-    match (ours, theirs) {
-        (Shape::Paper, Shape::Rock) => Round::Win as u32 + *ours as u32,
-        (Shape::Rock, Shape::Scissor) => Round::Win as u32 + *ours as u32,
-        (Shape::Scissor, Shape::Paper) => Round::Win as u32 + *ours as u32,
-        _ => Round::Lose as u32 + *ours as u32,
-    }
+fn calculate_score(rules: &GameRules, round: &ShapeRound) -> Score {
+    rules.score(round.theirs, round.ours)
 }
 
-fn part1(input: &Input) -> anyhow::Result<Score> {
-    let score = input.iter().map(calculate_score).sum();
+fn part1(rules: &GameRules, rounds: &[ShapeRound]) -> anyhow::Result<Score> {
+    let score = rounds.iter().map(|r| calculate_score(rules, r)).sum();
     Ok(score)
 }
 
-fn calculate_move(shapes: &ShapeTuple) -> ShapeTuple {
-    let (theirs, _, result) = shapes;
-    let ours = match (result, theirs) {
-        (Round::Lose, Shape::Rock) => Shape::Scissor,
-        (Round::Lose, Shape::Paper) => Shape::Rock,
-        (Round::Lose, Shape::Scissor) => Shape::Paper,
-        (Round::Draw, _) => *theirs,
-        (Round::Win, Shape::Rock) => Shape::Paper,
-        (Round::Win, Shape::Paper) => Shape::Scissor,
-        (Round::Win, Shape::Scissor) => Shape::Rock,
-    };
-
-    (*theirs, ours, *result)
+fn calculate_move(rules: &GameRules, round: &OutcomeRound) -> ShapeRound {
+    ShapeRound {
+        theirs: round.theirs,
+        ours: rules.choose(round.theirs, round.outcome),
+    }
 }
 
-fn part2(input: &Input) -> anyhow::Result<u32> {
-    let score = input
+fn part2(rules: &GameRules, rounds: &[OutcomeRound]) -> anyhow::Result<u32> {
+    let score = rounds
         .iter()
-        .map(calculate_move)
-        .map(|s| calculate_score(&s))
+        .map(|r| calculate_move(rules, r))
+        .map(|r| calculate_score(rules, &r))
         .sum();
     Ok(score)
 }
 
+fn transcript_part1<'a>(rules: &'a GameRules, rounds: &[ShapeRound]) -> Transcript<'a> {
+    Transcript::new(rules, rounds.iter().copied())
+}
+
+fn transcript_part2<'a>(rules: &'a GameRules, rounds: &[OutcomeRound]) -> Transcript<'a> {
+    Transcript::new(rules, rounds.iter().map(|r| calculate_move(rules, r)))
+}
+
 fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    let rules = match flag("--rpsls") {
+        true => GameRules::rock_paper_scissors_lizard_spock(),
+        false => GameRules::rock_paper_scissors(),
+    };
+    let symbols = match args.iter().find(|arg| !arg.starts_with("--")) {
+        Some(path) => SymbolMap::from_config(&std::fs::read_to_string(path)?, &rules)?,
+        None => SymbolMap::standard(&rules)?,
+    };
     let input = include_str!("input.txt");
     let input = parse(input)?;
-    println!("Part 1: {}", part1(&input)?);
-    println!("Part 2: {}", part2(&input)?);
+    let shapes = input.as_shapes(&symbols)?;
+    let outcomes = input.as_outcomes(&symbols)?;
+    println!("Part 1: {}", part1(&rules, &shapes)?);
+    println!("Part 2: {}", part2(&rules, &outcomes)?);
+    if flag("--transcript") {
+        println!(
+            "\nPart 1 transcript:\n{}",
+            transcript_part1(&rules, &shapes)
+        );
+        println!(
+            "\nPart 2 transcript:\n{}",
+            transcript_part2(&rules, &outcomes)
+        );
+    }
+    if flag("--analyze") {
+        let analysis = Analysis::new(&rules, &symbols, &input)?;
+        println!("\nBest possible: {}", analysis.best);
+        println!("Worst possible: {}", analysis.worst);
+        for interpretation in &analysis.interpretations {
+            println!(
+                "{}: {}",
+                interpretation.describe(&rules),
+                interpretation.total
+            );
+        }
+        if let Some(best) = analysis.best_interpretation() {
+            println!("Highest scoring reading: {}", best.describe(&rules));
+        }
+    }
     Ok(())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use analysis::AnalysisError;
+    use rules::RulesError;
+    use symbols::ParseError;
     const INPUT: &str = include_str!("input_test.txt");
 
     #[test]
     fn test_part1() {
-        let res = part1(&parse(INPUT).unwrap()).unwrap();
+        let rules = GameRules::rock_paper_scissors();
+        let input = parse(INPUT).unwrap();
+        let shapes = input
+            .as_shapes(&SymbolMap::standard(&rules).unwrap())
+            .unwrap();
+        let res = part1(&rules, &shapes).unwrap();
         assert_eq!(res, 15)
     }
 
     #[test]
     fn test_part2() {
-        let res = part2(&parse(INPUT).unwrap()).unwrap();
+        let rules = GameRules::rock_paper_scissors();
+        let input = parse(INPUT).unwrap();
+        let outcomes = input
+            .as_outcomes(&SymbolMap::standard(&rules).unwrap())
+            .unwrap();
+        let res = part2(&rules, &outcomes).unwrap();
         assert_eq!(res, 12)
     }
+
+    #[test]
+    fn test_alternate_encoding() {
+        let rules = GameRules::rock_paper_scissors();
+        let symbols = SymbolMap::from_config(
+            "# single-letter names\n\
+             opponent R Rock\nopponent P Paper\nopponent S Scissors\n\
+             shape l Rock\nshape d Paper\nshape w Scissors\n\
+             outcome l Lose\noutcome d Draw\noutcome w Win\n",
+            &rules,
+        )
+        .unwrap();
+        let input = parse("R d\nP l\nS w").unwrap();
+        let shapes = input.as_shapes(&symbols).unwrap();
+        let outcomes = input.as_outcomes(&symbols).unwrap();
+        assert_eq!(part1(&rules, &shapes).unwrap(), 15);
+        assert_eq!(part2(&rules, &outcomes).unwrap(), 12);
+    }
+
+    #[test]
+    fn test_unknown_symbol_names_line() {
+        let symbols = SymbolMap::standard(&GameRules::rock_paper_scissors()).unwrap();
+        let input = parse("A Y\nB Q\nC Z").unwrap();
+        assert_eq!(
+            input.as_shapes(&symbols).unwrap_err(),
+            ParseError::UnknownResponse {
+                line: 2,
+                symbol: "Q".to_owned()
+            }
+        );
+        let err = parse("A Y\nB\nC Z").unwrap_err();
+        assert_eq!(
+            err.downcast::<ParseError>().unwrap(),
+            ParseError::Malformed {
+                line: 2,
+                text: "B".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn test_interpretations_are_independent() {
+        let rules = GameRules::rock_paper_scissors();
+        let symbols = SymbolMap::new()
+            .with_opponent("A", rules.shape("Rock").unwrap())
+            .with_shape("X", rules.shape("Paper").unwrap());
+        let input = parse("A X").unwrap();
+        assert!(input.as_shapes(&symbols).is_ok());
+        assert!(input.as_outcomes(&symbols).is_err());
+    }
+
+    #[test]
+    fn test_invalid_config_names_line() {
+        let err = SymbolMap::from_config(
+            "opponent A Rock\nshape X Boulder",
+            &GameRules::rock_paper_scissors(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ParseError::InvalidMapping { line: 2, .. }));
+    }
+
+    #[test]
+    fn test_rpsls() {
+        let rules = GameRules::rock_paper_scissors_lizard_spock();
+        let symbols = SymbolMap::standard(&rules)
+            .unwrap()
+            .with_opponent("D", rules.shape("Lizard").unwrap())
+            .with_opponent("E", rules.shape("Spock").unwrap());
+        let input = parse("D Y\nE X\nA Z").unwrap();
+        // Lizard eats Paper, Spock vaporizes Rock, Rock crushes Scissors.
+        let shapes = input.as_shapes(&symbols).unwrap();
+        assert_eq!(part1(&rules, &shapes).unwrap(), 2 + 1 + 3);
+        // Draw with Lizard, lose to Spock with Rock, beat Rock with Paper.
+        let outcomes = input.as_outcomes(&symbols).unwrap();
+        assert_eq!(part2(&rules, &outcomes).unwrap(), (4 + 3) + 1 + (2 + 6));
+    }
+    #[test]
+    fn test_standard_symbols_follow_shape_names() {
+        let rules = GameRules::new(
+            &[("Paper", 2), ("Rock", 1), ("Scissors", 3)],
+            &[
+                ("Rock", "Scissors"),
+                ("Paper", "Rock"),
+                ("Scissors", "Paper"),
+            ],
+        )
+        .unwrap();
+        let symbols = SymbolMap::standard(&rules).unwrap();
+        assert_eq!(symbols.opponent("A"), rules.shape("Rock"));
+        assert_eq!(symbols.shape("Y"), rules.shape("Paper"));
+        assert_eq!(
+            part1(&rules, &parse(INPUT).unwrap().as_shapes(&symbols).unwrap()).unwrap(),
+            15
+        );
+
+        let rules = GameRules::new(
+            &[("Lizard", 1), ("Spock", 2), ("Paper", 3)],
+            &[("Lizard", "Spock"), ("Spock", "Paper"), ("Paper", "Lizard")],
+        )
+        .unwrap();
+        assert_eq!(
+            SymbolMap::standard(&rules).unwrap_err(),
+            RulesError::UnknownShape("Rock".to_owned())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "belongs to a different rule set")]
+    fn test_shapes_are_tied_to_their_rules() {
+        let rpsls = GameRules::rock_paper_scissors_lizard_spock();
+        let spock = rpsls.shape("Spock").unwrap();
+        GameRules::rock_paper_scissors().points(spock);
+    }
+
+    #[test]
+    fn test_rules_are_validated() {
+        assert_eq!(
+            GameRules::new(
+                &[("Rock", 1), ("Paper", 2)],
+                &[("Rock", "Paper"), ("Paper", "Rock")]
+            )
+            .unwrap_err(),
+            RulesError::Contradiction("Paper".to_owned(), "Rock".to_owned())
+        );
+        assert_eq!(
+            GameRules::new(
+                &[("Rock", 1), ("Paper", 2), ("Scissors", 3)],
+                &[("Paper", "Rock"), ("Scissors", "Paper")]
+            )
+            .unwrap_err(),
+            RulesError::Undecided("Rock".to_owned(), "Scissors".to_owned())
+        );
+        assert_eq!(
+            GameRules::new(
+                &[("Rock", 1), ("Paper", 2), ("Scissors", 3)],
+                &[
+                    ("Paper", "Rock"),
+                    ("Paper", "Scissors"),
+                    ("Scissors", "Rock")
+                ]
+            )
+            .unwrap_err(),
+            RulesError::NeverWins("Rock".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_transcript() {
+        let rules = GameRules::rock_paper_scissors();
+        let input = parse(INPUT).unwrap();
+        let symbols = SymbolMap::standard(&rules).unwrap();
+        let transcript = transcript_part1(&rules, &input.as_shapes(&symbols).unwrap());
+        assert_eq!(
+            transcript.rounds[0],
+            transcript::RoundReport {
+                theirs: rules.shape("Rock").unwrap(),
+                ours: rules.shape("Paper").unwrap(),
+                outcome: Round::Win,
+                shape_points: 2,
+                outcome_points: 6,
+            }
+        );
+        assert_eq!(
+            transcript.summary(),
+            transcript::Summary {
+                wins: 1,
+                draws: 1,
+                losses: 1,
+                total: 15,
+            }
+        );
+        assert_eq!(
+            transcript.to_string(),
+            "Round 1: Paper vs Rock -> Win (2 + 6 = 8)\n\
+             Round 2: Rock vs Paper -> Lose (1 + 0 = 1)\n\
+             Round 3: Scissors vs Scissors -> Draw (3 + 3 = 6)\n\
+             1 wins, 1 draws, 1 losses, total 15"
+        );
+        let outcomes = input.as_outcomes(&symbols).unwrap();
+        assert_eq!(transcript_part2(&rules, &outcomes).summary().total, 12);
+    }
+
+    #[test]
+    fn test_analysis() {
+        let rules = GameRules::rock_paper_scissors();
+        let symbols = SymbolMap::standard(&rules).unwrap();
+        let analysis = Analysis::new(&rules, &symbols, &parse(INPUT).unwrap()).unwrap();
+        assert_eq!(analysis.best, 8 + 9 + 7);
+        assert_eq!(analysis.worst, 3 + 1 + 2);
+        assert_eq!(analysis.interpretations.len(), 6);
+        assert_eq!(analysis.interpretations[0].total, 15);
+        assert!(analysis
+            .interpretations
+            .iter()
+            .all(|i| (analysis.worst..=analysis.best).contains(&i.total)));
+
+        let best = analysis.best_interpretation().unwrap();
+        assert_eq!(best.total, analysis.best);
+        assert_eq!(best.describe(&rules), "X=Scissors Y=Paper Z=Rock");
+
+        let guide = parse(&format!("{INPUT}\nA W")).unwrap();
+        let err = Analysis::new(&rules, &symbols, &guide).unwrap_err();
+        assert_eq!(
+            err,
+            AnalysisError::TooManyResponses {
+                found: 4,
+                shapes: 3
+            }
+        );
+    }
 }
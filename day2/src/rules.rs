@@ -0,0 +1,207 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use thiserror::Error;
+
+use crate::{Round, Score};
+
+/// A shape of one particular rule set. Shapes are only obtained from a
+/// `GameRules` and only mean something to the rules they came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Shape {
+    rules: usize,
+    index: usize,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RulesError {
+    #[error("a game needs at least two shapes")]
+    TooFewShapes,
+    #[error("shape {0:?} is defined more than once")]
+    DuplicateShape(String),
+    #[error("unknown shape {0:?}")]
+    UnknownShape(String),
+    #[error("shape {0:?} cannot beat itself")]
+    BeatsItself(String),
+    #[error("{0:?} and {1:?} both beat each other")]
+    Contradiction(String, String),
+    #[error("no winner defined between {0:?} and {1:?}")]
+    Undecided(String, String),
+    #[error("shape {0:?} can never win")]
+    NeverWins(String),
+    #[error("shape {0:?} can never lose")]
+    NeverLoses(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct GameRules {
+    id: usize,
+    names: Vec<String>,
+    points: Vec<Score>,
+    beats: Vec<Vec<bool>>,
+}
+
+impl GameRules {
+    /// Builds a rule set from `(name, points)` shapes and `(winner, loser)` pairs.
+    ///
+    /// Every pair of distinct shapes must have exactly one winner, and every shape
+    /// must both win and lose against something so that any outcome can be chosen.
+    pub fn new(shapes: &[(&str, Score)], beats: &[(&str, &str)]) -> Result<Self, RulesError> {
+        if shapes.len() < 2 {
+            return Err(RulesError::TooFewShapes);
+        }
+        let mut names: Vec<String> = Vec::with_capacity(shapes.len());
+        for (name, _) in shapes {
+            if names.iter().any(|n| n == name) {
+                return Err(RulesError::DuplicateShape(name.to_string()));
+            }
+            names.push(name.to_string());
+        }
+        let index = |name: &str| {
+            names
+                .iter()
+                .position(|n| n == name)
+                .ok_or_else(|| RulesError::UnknownShape(name.to_owned()))
+        };
+
+        let mut matrix = vec![vec![false; shapes.len()]; shapes.len()];
+        for (winner, loser) in beats {
+            let (w, l) = (index(winner)?, index(loser)?);
+            if w == l {
+                return Err(RulesError::BeatsItself(winner.to_string()));
+            }
+            if matrix[l][w] {
+                return Err(RulesError::Contradiction(
+                    winner.to_string(),
+                    loser.to_string(),
+                ));
+            }
+            matrix[w][l] = true;
+        }
+
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let rules = Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            points: shapes.iter().map(|(_, points)| *points).collect(),
+            beats: matrix,
+            names,
+        };
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    fn validate(&self) -> Result<(), RulesError> {
+        for a in self.shapes() {
+            for b in self.shapes().filter(|b| *b > a) {
+                if !self.beats(a, b) && !self.beats(b, a) {
+                    return Err(RulesError::Undecided(
+                        self.name(a).to_owned(),
+                        self.name(b).to_owned(),
+                    ));
+                }
+            }
+            if !self.shapes().any(|b| self.beats(a, b)) {
+                return Err(RulesError::NeverWins(self.name(a).to_owned()));
+            }
+            if !self.shapes().any(|b| self.beats(b, a)) {
+                return Err(RulesError::NeverLoses(self.name(a).to_owned()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn rock_paper_scissors() -> Self {
+        Self::new(
+            &[("Rock", 1), ("Paper", 2), ("Scissors", 3)],
+            &[
+                ("Rock", "Scissors"),
+                ("Paper", "Rock"),
+                ("Scissors", "Paper"),
+            ],
+        )
+        .expect("Rock-Paper-Scissors rules are consistent")
+    }
+
+    pub fn rock_paper_scissors_lizard_spock() -> Self {
+        Self::new(
+            &[
+                ("Rock", 1),
+                ("Paper", 2),
+                ("Scissors", 3),
+                ("Lizard", 4),
+                ("Spock", 5),
+            ],
+            &[
+                ("Scissors", "Paper"),
+                ("Paper", "Rock"),
+                ("Rock", "Lizard"),
+                ("Lizard", "Spock"),
+                ("Spock", "Scissors"),
+                ("Scissors", "Lizard"),
+                ("Lizard", "Paper"),
+                ("Paper", "Spock"),
+                ("Spock", "Rock"),
+                ("Rock", "Scissors"),
+            ],
+        )
+        .expect("Rock-Paper-Scissors-Lizard-Spock rules are consistent")
+    }
+
+    pub fn shapes(&self) -> impl Iterator<Item = Shape> + '_ {
+        (0..self.names.len()).map(|index| self.shape_at(index))
+    }
+
+    pub fn shape(&self, name: &str) -> Option<Shape> {
+        let index = self.names.iter().position(|n| n == name)?;
+        Some(self.shape_at(index))
+    }
+
+    fn shape_at(&self, index: usize) -> Shape {
+        Shape {
+            rules: self.id,
+            index,
+        }
+    }
+
+    /// Panics if `shape` belongs to another rule set.
+    fn index(&self, shape: Shape) -> usize {
+        assert_eq!(
+            shape.rules, self.id,
+            "shape {shape:?} belongs to a different rule set"
+        );
+        shape.index
+    }
+
+    pub fn name(&self, shape: Shape) -> &str {
+        &self.names[self.index(shape)]
+    }
+
+    pub fn points(&self, shape: Shape) -> Score {
+        self.points[self.index(shape)]
+    }
+
+    pub fn beats(&self, winner: Shape, loser: Shape) -> bool {
+        self.beats[self.index(winner)][self.index(loser)]
+    }
+
+    pub fn outcome(&self, theirs: Shape, ours: Shape) -> Round {
+        if self.beats(ours, theirs) {
+            Round::Win
+        } else if self.beats(theirs, ours) {
+            Round::Lose
+        } else {
+            Round::Draw
+        }
+    }
+
+    pub fn score(&self, theirs: Shape, ours: Shape) -> Score {
+        self.outcome(theirs, ours) as Score + self.points(ours)
+    }
+
+    /// Picks a shape reaching `desired` against `theirs`, preferring the first
+    /// defined shape when several qualify.
+    pub fn choose(&self, theirs: Shape, desired: Round) -> Shape {
+        self.shapes()
+            .find(|ours| self.outcome(theirs, *ours) == desired)
+            .expect("validated rules allow every outcome")
+    }
+}
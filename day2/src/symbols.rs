@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{
+    rules::{GameRules, RulesError, Shape},
+    Round,
+};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ParseError {
+    #[error("line {line}: expected two symbols separated by a space, got {text:?}")]
+    Malformed { line: usize, text: String },
+    #[error("line {line}: unknown opponent symbol {symbol:?}")]
+    UnknownOpponent { line: usize, symbol: String },
+    #[error("line {line}: unknown response symbol {symbol:?}")]
+    UnknownResponse { line: usize, symbol: String },
+    #[error("line {line}: invalid mapping {text:?}")]
+    InvalidMapping { line: usize, text: String },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SymbolMap {
+    opponent: HashMap<String, Shape>,
+    shapes: HashMap<String, Shape>,
+    outcomes: HashMap<String, Round>,
+}
+
+impl SymbolMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The puzzle's mapping, resolving Rock, Paper and Scissors by name in `rules`.
+    pub fn standard(rules: &GameRules) -> Result<Self, RulesError> {
+        let shape = |name: &str| {
+            rules
+                .shape(name)
+                .ok_or_else(|| RulesError::UnknownShape(name.to_owned()))
+        };
+        let (rock, paper, scissors) = (shape("Rock")?, shape("Paper")?, shape("Scissors")?);
+        Ok(Self::new()
+            .with_opponent("A", rock)
+            .with_opponent("B", paper)
+            .with_opponent("C", scissors)
+            .with_shape("X", rock)
+            .with_shape("Y", paper)
+            .with_shape("Z", scissors)
+            .with_outcome("X", Round::Lose)
+            .with_outcome("Y", Round::Draw)
+            .with_outcome("Z", Round::Win))
+    }
+
+    pub fn with_opponent(mut self, symbol: &str, shape: Shape) -> Self {
+        self.opponent.insert(symbol.to_owned(), shape);
+        self
+    }
+
+    pub fn with_shape(mut self, symbol: &str, shape: Shape) -> Self {
+        self.shapes.insert(symbol.to_owned(), shape);
+        self
+    }
+
+    pub fn with_outcome(mut self, symbol: &str, outcome: Round) -> Self {
+        self.outcomes.insert(symbol.to_owned(), outcome);
+        self
+    }
+
+    /// Reads one mapping per line, e.g. `opponent A Rock`, `shape X Rock` or
+    /// `outcome X Lose`, resolving shape names against `rules`. Blank lines and
+    /// lines starting with `#` are ignored.
+    pub fn from_config(config: &str, rules: &GameRules) -> Result<Self, ParseError> {
+        config
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .try_fold(Self::new(), |map, (line, text)| {
+                let invalid = || ParseError::InvalidMapping {
+                    line,
+                    text: text.to_owned(),
+                };
+                let mut parts = text.split_whitespace();
+                let (kind, symbol, value) = match (parts.next(), parts.next(), parts.next()) {
+                    (Some(kind), Some(symbol), Some(value)) if parts.next().is_none() => {
+                        (kind, symbol, value)
+                    }
+                    _ => return Err(invalid()),
+                };
+                let shape = || rules.shape(value).ok_or_else(invalid);
+                let map = match kind {
+                    "opponent" => map.with_opponent(symbol, shape()?),
+                    "shape" => map.with_shape(symbol, shape()?),
+                    "outcome" => map.with_outcome(symbol, value.parse().map_err(|_| invalid())?),
+                    _ => return Err(invalid()),
+                };
+                Ok(map)
+            })
+    }
+
+    pub fn opponent(&self, symbol: &str) -> Option<Shape> {
+        self.opponent.get(symbol).copied()
+    }
+
+    pub fn shape(&self, symbol: &str) -> Option<Shape> {
+        self.shapes.get(symbol).copied()
+    }
+
+    pub fn outcome(&self, symbol: &str) -> Option<Round> {
+        self.outcomes.get(symbol).copied()
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt;
+
+use crate::{
+    guide::ShapeRound,
+    rules::{GameRules, Shape},
+    Round, Score,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundReport {
+    pub theirs: Shape,
+    pub ours: Shape,
+    pub outcome: Round,
+    pub shape_points: Score,
+    pub outcome_points: Score,
+}
+
+impl RoundReport {
+    pub fn new(rules: &GameRules, theirs: Shape, ours: Shape) -> Self {
+        let outcome = rules.outcome(theirs, ours);
+        Self {
+            theirs,
+            ours,
+            outcome,
+            shape_points: rules.points(ours),
+            outcome_points: outcome as Score,
+        }
+    }
+
+    pub fn total(&self) -> Score {
+        self.shape_points + self.outcome_points
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    pub total: Score,
+}
+
+#[derive(Debug, Clone)]
+pub struct Transcript<'a> {
+    rules: &'a GameRules,
+    pub rounds: Vec<RoundReport>,
+}
+
+impl<'a> Transcript<'a> {
+    pub fn new(rules: &'a GameRules, rounds: impl IntoIterator<Item = ShapeRound>) -> Self {
+        let rounds = rounds
+            .into_iter()
+            .map(|round| RoundReport::new(rules, round.theirs, round.ours))
+            .collect();
+        Self { rules, rounds }
+    }
+
+    pub fn summary(&self) -> Summary {
+        self.rounds
+            .iter()
+            .fold(Summary::default(), |mut summary, round| {
+                match round.outcome {
+                    Round::Win => summary.wins += 1,
+                    Round::Draw => summary.draws += 1,
+                    Round::Lose => summary.losses += 1,
+                }
+                summary.total += round.total();
+                summary
+            })
+    }
+}
+
+impl fmt::Display for Transcript<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, round) in self.rounds.iter().enumerate() {
+            writeln!(
+                f,
+                "Round {}: {} vs {} -> {:?} ({} + {} = {})",
+                index + 1,
+                self.rules.name(round.ours),
+                self.rules.name(round.theirs),
+                round.outcome,
+                round.shape_points,
+                round.outcome_points,
+                round.total(),
+            )?;
+        }
+        let summary = self.summary();
+        write!(
+            f,
+            "{} wins, {} draws, {} losses, total {}",
+            summary.wins, summary.draws, summary.losses, summary.total
+        )
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[allow(dead_code)]
+#[path = "../src/items.rs"]
+mod items;
+
+use items::{calculate_priority, ItemSet};
+
+const INPUT: &str = include_str!("../src/input.txt");
+
+fn rucksacks() -> Vec<Vec<char>> {
+    INPUT.lines().map(|line| line.chars().collect()).collect()
+}
+
+fn hash_set(items: &[char]) -> HashSet<char> {
+    items.iter().copied().collect()
+}
+
+fn shared_hash_set(rucksacks: &[Vec<char>]) -> u32 {
+    rucksacks
+        .iter()
+        .map(|items| {
+            let (first, second) = items.split_at(items.len() / 2);
+            hash_set(first)
+                .intersection(&hash_set(second))
+                .map(|item| calculate_priority(*item).unwrap() as u32)
+                .sum::<u32>()
+        })
+        .sum()
+}
+
+fn shared_item_set(rucksacks: &[Vec<char>]) -> u32 {
+    rucksacks
+        .iter()
+        .map(|items| {
+            let (first, second) = items.split_at(items.len() / 2);
+            let first = ItemSet::new(first.iter().copied()).unwrap();
+            let second = ItemSet::new(second.iter().copied()).unwrap();
+            first
+                .intersection(second)
+                .iter()
+                .map(|item| calculate_priority(item).unwrap() as u32)
+                .sum::<u32>()
+        })
+        .sum()
+}
+
+fn badges_hash_set(rucksacks: &[Vec<char>]) -> u32 {
+    rucksacks
+        .chunks(3)
+        .map(|group| {
+            let badge = group
+                .iter()
+                .map(|items| hash_set(items))
+                .reduce(|badge, set| badge.intersection(&set).copied().collect())
+                .unwrap_or_default();
+            badge
+                .iter()
+                .map(|item| calculate_priority(*item).unwrap() as u32)
+                .sum::<u32>()
+        })
+        .sum()
+}
+
+fn badges_item_set(rucksacks: &[Vec<char>]) -> u32 {
+    rucksacks
+        .chunks(3)
+        .map(|group| {
+            let badge = group
+                .iter()
+                .map(|items| ItemSet::new(items.iter().copied()).unwrap())
+                .reduce(ItemSet::intersection)
+                .unwrap_or_default();
+            badge
+                .iter()
+                .map(|item| calculate_priority(item).unwrap() as u32)
+                .sum::<u32>()
+        })
+        .sum()
+}
+
+fn bench_shared_items(c: &mut Criterion) {
+    let rucksacks = rucksacks();
+    let mut group = c.benchmark_group("shared items");
+    group.bench_function("hash set", |b| {
+        b.iter(|| shared_hash_set(black_box(&rucksacks)))
+    });
+    group.bench_function("item set", |b| {
+        b.iter(|| shared_item_set(black_box(&rucksacks)))
+    });
+    group.finish();
+}
+
+fn bench_badges(c: &mut Criterion) {
+    let rucksacks = rucksacks();
+    let mut group = c.benchmark_group("group badges");
+    group.bench_function("hash set", |b| {
+        b.iter(|| badges_hash_set(black_box(&rucksacks)))
+    });
+    group.bench_function("item set", |b| {
+        b.iter(|| badges_item_set(black_box(&rucksacks)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_shared_items, bench_badges);
+criterion_main!(benches);
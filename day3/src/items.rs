@@ -0,0 +1,66 @@
+use thiserror::Error;
+
+pub type Item = char;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("invalid item {0:?}")]
+pub struct InvalidItem(pub Item);
+
+const LOWER_CASE_START: u8 = 1;
+const UPPER_CASE_START: u8 = 27;
+
+/// The priority of a letter, or `None` for any other character.
+pub fn calculate_priority(item: Item) -> Option<u8> {
+    match item {
+        'A'..='Z' => Some((item as u8 - b'A') + UPPER_CASE_START),
+        'a'..='z' => Some((item as u8 - b'a') + LOWER_CASE_START),
+        _ => None,
+    }
+}
+
+fn item_from_priority(priority: u8) -> Item {
+    if priority >= UPPER_CASE_START {
+        (b'A' + priority - UPPER_CASE_START) as char
+    } else {
+        (b'a' + priority - LOWER_CASE_START) as char
+    }
+}
+
+/// A set of items with one bit per priority, so bit `n` holds the item whose
+/// priority is `n + 1`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    /// Collects `items` into a set, failing on the first one that is not a letter.
+    pub fn new(items: impl IntoIterator<Item = Item>) -> Result<Self, InvalidItem> {
+        items.into_iter().try_fold(Self::default(), |set, item| {
+            let priority = calculate_priority(item).ok_or(InvalidItem(item))?;
+            Ok(Self(set.0 | 1 << (priority - 1)))
+        })
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = Item> {
+        let mut bits = self.0;
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let bit = bits.trailing_zeros() as u8;
+            bits &= bits - 1;
+            Some(item_from_priority(bit + 1))
+        })
+    }
+}
@@ -1,12 +1,64 @@
-use std::collections::HashSet;
+mod items;
+
+use std::fmt;
 
 use anyhow::Result;
+use thiserror::Error;
+
+use items::{calculate_priority, InvalidItem, Item, ItemSet};
 
 type Input = [Rucksack];
-type Item = char;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+enum RucksackError {
+    #[error("line {line}: rucksack has an odd number of items ({len})")]
+    OddLength { line: usize, len: usize },
+    #[error("line {line}: invalid item {item:?}")]
+    InvalidItem { line: usize, item: char },
+    #[error("line {line}: compartments share no item")]
+    NoSharedItem { line: usize },
+    #[error("line {line}: compartments share several items {items:?}")]
+    SeveralSharedItems { line: usize, items: String },
+    #[error("group {group}: rucksacks have no common badge")]
+    NoBadge { group: usize },
+    #[error("group {group}: rucksacks have several candidate badges {items:?}")]
+    SeveralBadges { group: usize, items: String },
+    #[error("group size must be at least 1")]
+    InvalidGroupSize,
+    #[error("group {group}: expected {size} rucksacks, found {len}")]
+    IncompleteGroup {
+        group: usize,
+        size: usize,
+        len: usize,
+    },
+}
+
+const GROUP_SIZE: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Grouping {
+    Chunks,
+    Windows,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Badge {
+    item: Item,
+    priority: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SharedItem {
+    line: usize,
+    item: Item,
+    priority: u8,
+    first_positions: Vec<usize>,
+    second_positions: Vec<usize>,
+}
 
 #[derive(Debug)]
 struct Rucksack {
+    line: usize,
     items: Vec<Item>,
 }
 
@@ -16,17 +68,31 @@ struct Compartment<'a> {
 }
 
 impl<'a> Compartment<'a> {
-    fn as_set(&self) -> HashSet<Item> {
-        self.items.iter().fold(HashSet::new(), |mut map, item| {
-            map.insert(*item);
-            map
-        })
+    fn as_set(&self) -> ItemSet {
+        ItemSet::new(self.items.iter().copied()).expect("rucksack items are letters")
+    }
+
+    fn positions(&self, item: Item) -> Vec<usize> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| **i == item)
+            .map(|(position, _)| position)
+            .collect()
     }
 }
 
 impl Rucksack {
-    fn new(items: Vec<Item>) -> Self {
-        Self { items }
+    fn new(line: usize, items: Vec<Item>) -> Result<Self, RucksackError> {
+        ItemSet::new(items.iter().copied())
+            .map_err(|InvalidItem(item)| RucksackError::InvalidItem { line, item })?;
+        if !items.len().is_multiple_of(2) {
+            return Err(RucksackError::OddLength {
+                line,
+                len: items.len(),
+            });
+        }
+        Ok(Self { line, items })
     }
 
     fn get_first_compartment(&self) -> Compartment<'_> {
@@ -43,73 +109,191 @@ impl Rucksack {
         }
     }
 
-    fn as_set(&self) -> HashSet<Item> {
-        self.items.iter().fold(HashSet::new(), |mut map, item| {
-            map.insert(*item);
-            map
-        })
+    fn as_set(&self) -> ItemSet {
+        self.get_first_compartment()
+            .as_set()
+            .union(self.get_second_compartment().as_set())
+    }
+
+    fn shared_item(&self) -> Result<Item, RucksackError> {
+        let shared = self
+            .get_first_compartment()
+            .as_set()
+            .intersection(self.get_second_compartment().as_set());
+        match shared.len() {
+            0 => Err(RucksackError::NoSharedItem { line: self.line }),
+            1 => Ok(shared.iter().next().unwrap()),
+            _ => Err(RucksackError::SeveralSharedItems {
+                line: self.line,
+                items: shared.iter().collect(),
+            }),
+        }
     }
 }
 
 fn parse(input: &str) -> Result<Vec<Rucksack>> {
     let res = input
         .lines()
-        .map(|line| Rucksack::new(line.chars().collect()))
-        .collect();
+        .enumerate()
+        .map(|(index, line)| Rucksack::new(index + 1, line.chars().collect()))
+        .collect::<Result<_, _>>()?;
     Ok(res)
 }
 
-const LOWER_CASE_START: u8 = 1;
-const UPPER_CASE_START: u8 = 27;
-
-fn calculate_priority(item: Item) -> u8 {
-    let item = item as u8;
-    if (b'A'..=b'Z').contains(&item) {
-        (item - b'A') + UPPER_CASE_START
-    } else {
-        (item - b'a') + LOWER_CASE_START
-    }
+fn find_shared_items(input: &Input) -> Result<Vec<SharedItem>, RucksackError> {
+    input
+        .iter()
+        .map(|rucksack| {
+            let item = rucksack.shared_item()?;
+            Ok(SharedItem {
+                line: rucksack.line,
+                item,
+                priority: calculate_priority(item).expect("shared items are letters"),
+                first_positions: rucksack.get_first_compartment().positions(item),
+                second_positions: rucksack.get_second_compartment().positions(item),
+            })
+        })
+        .collect()
 }
 
 fn part1(input: &Input) -> Result<u32> {
-    let intersections = input.iter().fold(Vec::new(), |mut vec, item| {
-        let first_comp = item.get_first_compartment().as_set();
-        let second_comp = item.get_second_compartment().as_set();
-        vec.extend(first_comp.intersection(&second_comp));
-        vec
-    });
-    let sum: u8 = intersections
+    let sum = find_shared_items(input)?
         .iter()
-        .map(|item| calculate_priority(*item))
+        .map(|shared| shared.priority as u32)
         .sum();
-    Ok(sum as u32)
+    Ok(sum)
 }
 
-fn find_group_badge(rucksacks: &[Rucksack]) -> u32 {
-    let badge_iter = rucksacks
+fn find_group_badge(group: usize, rucksacks: &[Rucksack]) -> Result<Item, RucksackError> {
+    let badge = rucksacks
         .iter()
-        .map(|r| r.as_set())
-        .fold(HashSet::new(), |mut badge, item| {
-            if badge.is_empty() {
-                badge = item.clone();
-            }
-            badge.intersection(&item).copied().collect()
+        .map(Rucksack::as_set)
+        .reduce(ItemSet::intersection)
+        .unwrap_or_default();
+    match badge.len() {
+        0 => Err(RucksackError::NoBadge { group }),
+        1 => Ok(badge.iter().next().unwrap()),
+        _ => Err(RucksackError::SeveralBadges {
+            group,
+            items: badge.iter().collect(),
+        }),
+    }
+}
+
+fn find_badges(
+    input: &Input,
+    size: usize,
+    grouping: Grouping,
+) -> Result<Vec<Badge>, RucksackError> {
+    if size == 0 {
+        return Err(RucksackError::InvalidGroupSize);
+    }
+    if input.is_empty() {
+        return Ok(Vec::new());
+    }
+    let groups: Vec<&[Rucksack]> = match grouping {
+        Grouping::Chunks => input.chunks(size).collect(),
+        Grouping::Windows => input.windows(size).collect(),
+    };
+    if input.len() < size || (grouping == Grouping::Chunks && !input.len().is_multiple_of(size)) {
+        return Err(RucksackError::IncompleteGroup {
+            group: groups.len().max(1),
+            size,
+            len: input.len() % size,
+        });
+    }
+    groups
+        .into_iter()
+        .enumerate()
+        .map(|(index, group)| {
+            let item = find_group_badge(index + 1, group)?;
+            Ok(Badge {
+                item,
+                priority: calculate_priority(item).expect("badges are letters"),
+            })
         })
-        .into_iter();
-    let badge = Vec::from_iter(badge_iter);
-    calculate_priority(*badge.first().unwrap()) as u32
+        .collect()
 }
 
 fn part2(input: &Input) -> Result<u32> {
-    let sum: u32 = input.chunks(3).map(find_group_badge).sum();
+    let sum = find_badges(input, GROUP_SIZE, Grouping::Chunks)?
+        .iter()
+        .map(|badge| badge.priority as u32)
+        .sum();
     Ok(sum)
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Explanation {
+    shared: Vec<SharedItem>,
+    badges: Vec<Badge>,
+}
+
+impl Explanation {
+    fn new(input: &Input, size: usize, grouping: Grouping) -> Result<Self, RucksackError> {
+        Ok(Self {
+            shared: find_shared_items(input)?,
+            badges: find_badges(input, size, grouping)?,
+        })
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let positions = |p: &[usize]| p.iter().map(usize::to_string).collect::<Vec<_>>().join(",");
+        for shared in &self.shared {
+            writeln!(
+                f,
+                "Rucksack {}: {} ({}) at {} in first compartment, {} in second",
+                shared.line,
+                shared.item,
+                shared.priority,
+                positions(&shared.first_positions),
+                positions(&shared.second_positions),
+            )?;
+        }
+        let total: u32 = self.shared.iter().map(|s| s.priority as u32).sum();
+        writeln!(f, "Shared item total: {}", total)?;
+        for (index, badge) in self.badges.iter().enumerate() {
+            writeln!(
+                f,
+                "Group {}: {} ({})",
+                index + 1,
+                badge.item,
+                badge.priority
+            )?;
+        }
+        let total: u32 = self.badges.iter().map(|b| b.priority as u32).sum();
+        write!(f, "Badge total: {}", total)
+    }
+}
+
 fn main() -> Result<()> {
     let input = include_str!("input.txt");
     let input = parse(input)?;
     println!("Part 1: {}", part1(&input)?);
     println!("Part 2: {}", part2(&input)?);
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let size = args
+        .iter()
+        .find_map(|arg| arg.strip_prefix("--group-size="))
+        .map(str::parse)
+        .transpose()?;
+    let sliding = args.iter().any(|arg| arg == "--sliding");
+    let grouping = match sliding {
+        true => Grouping::Windows,
+        false => Grouping::Chunks,
+    };
+    if args.iter().any(|arg| arg == "--explain") {
+        let explanation = Explanation::new(&input, size.unwrap_or(GROUP_SIZE), grouping)?;
+        println!("\n{}", explanation);
+    } else if size.is_some() || sliding {
+        let badges = find_badges(&input, size.unwrap_or(GROUP_SIZE), grouping)?;
+        for (index, badge) in badges.iter().enumerate() {
+            println!("Group {}: {} ({})", index + 1, badge.item, badge.priority);
+        }
+    }
     Ok(())
 }
 
@@ -129,4 +313,146 @@ mod test {
         let res = part2(&parse(INPUT).unwrap()).unwrap();
         assert_eq!(res, 70)
     }
+
+    #[test]
+    fn test_item_set() {
+        let first = ItemSet::new("vJrwpWtwJgWr".chars()).unwrap();
+        let second = ItemSet::new("hcsFMMfFFhFp".chars()).unwrap();
+        assert_eq!(first.intersection(second).iter().collect::<String>(), "p");
+        assert_eq!(
+            first.union(second).iter().collect::<String>(),
+            "cfghprstvwFJMW"
+        );
+        let all = ItemSet::new(('a'..='z').chain('A'..='Z')).unwrap();
+        assert_eq!(
+            all.iter().map(calculate_priority).collect::<Vec<_>>(),
+            (1..=52).map(Some).collect::<Vec<_>>()
+        );
+        assert_eq!(ItemSet::new("ab1".chars()), Err(InvalidItem('1')));
+        assert_eq!(calculate_priority('['), None);
+        assert_eq!(calculate_priority('`'), None);
+    }
+
+    #[test]
+    fn test_invalid_rucksacks() {
+        let err = |input| {
+            parse(input)
+                .unwrap_err()
+                .downcast::<RucksackError>()
+                .unwrap()
+        };
+        assert_eq!(
+            err("abca\nabc"),
+            RucksackError::OddLength { line: 2, len: 3 }
+        );
+        assert_eq!(
+            err("ab1a"),
+            RucksackError::InvalidItem { line: 1, item: '1' }
+        );
+
+        let err = |input| {
+            part1(&parse(input).unwrap())
+                .unwrap_err()
+                .downcast::<RucksackError>()
+                .unwrap()
+        };
+        assert_eq!(err("abcd"), RucksackError::NoSharedItem { line: 1 });
+        assert_eq!(
+            err("aaaa\nabba"),
+            RucksackError::SeveralSharedItems {
+                line: 2,
+                items: "ab".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_groups() {
+        let err = |input| {
+            part2(&parse(input).unwrap())
+                .unwrap_err()
+                .downcast::<RucksackError>()
+                .unwrap()
+        };
+        assert_eq!(err("aa\nbb\ncc"), RucksackError::NoBadge { group: 1 });
+        assert_eq!(
+            err("aa\naa\naa\nab\nab\nab"),
+            RucksackError::SeveralBadges {
+                group: 2,
+                items: "ab".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn test_group_size() {
+        let input = parse("abxa\nbcyb\nmnzm\nnopn").unwrap();
+        let badges = find_badges(&input, 2, Grouping::Chunks).unwrap();
+        assert_eq!(
+            badges,
+            vec![
+                Badge {
+                    item: 'b',
+                    priority: 2
+                },
+                Badge {
+                    item: 'n',
+                    priority: 14
+                },
+            ]
+        );
+        assert_eq!(
+            find_badges(&input[..3], 2, Grouping::Chunks).unwrap_err(),
+            RucksackError::IncompleteGroup {
+                group: 2,
+                size: 2,
+                len: 1
+            }
+        );
+        assert_eq!(find_badges(&input[..0], 2, Grouping::Chunks), Ok(vec![]));
+        assert_eq!(part2(&parse("").unwrap()).unwrap(), 0);
+        assert_eq!(
+            find_badges(&input, 0, Grouping::Chunks).unwrap_err(),
+            RucksackError::InvalidGroupSize
+        );
+    }
+
+    #[test]
+    fn test_sliding_groups() {
+        let input = parse("abxa\nbcyb\ncdzc").unwrap();
+        let badges = find_badges(&input, 2, Grouping::Windows).unwrap();
+        assert_eq!(
+            badges.iter().map(|badge| badge.item).collect::<String>(),
+            "bc"
+        );
+        assert_eq!(
+            find_badges(&input, 4, Grouping::Windows).unwrap_err(),
+            RucksackError::IncompleteGroup {
+                group: 1,
+                size: 4,
+                len: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_explanation() {
+        let input = parse(INPUT).unwrap();
+        let explanation = Explanation::new(&input, GROUP_SIZE, Grouping::Chunks).unwrap();
+        assert_eq!(
+            explanation.shared[0],
+            SharedItem {
+                line: 1,
+                item: 'p',
+                priority: 16,
+                first_positions: vec![4],
+                second_positions: vec![11],
+            }
+        );
+        assert_eq!(explanation.shared[1].first_positions, vec![13, 15]);
+        let text = explanation.to_string();
+        assert!(text.starts_with("Rucksack 1: p (16) at 4 in first compartment, 11 in second\n"));
+        assert!(text.contains("Shared item total: 157\n"));
+        assert!(text.ends_with("Group 2: Z (52)\nBadge total: 70"));
+    }
 }
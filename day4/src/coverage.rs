@@ -0,0 +1,89 @@
+use std::{collections::BTreeMap, fmt};
+
+use itertools::Itertools;
+
+use crate::interval::{Interval, Section};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    /// Sorted, disjoint ranges covering every assigned section.
+    pub ranges: Vec<Interval>,
+    pub covered: u64,
+    /// Unassigned sections between the first and last assigned one.
+    pub gaps: Vec<Interval>,
+    /// Sections assigned to `max_elves` elves, the highest count of any section.
+    pub busiest: Vec<Interval>,
+    pub max_elves: usize,
+}
+
+impl Coverage {
+    pub fn new(intervals: impl IntoIterator<Item = Interval>) -> Self {
+        let intervals = intervals.into_iter().sorted().collect_vec();
+        let ranges = intervals
+            .iter()
+            .copied()
+            .coalesce(|a, b| a.union(b).ok_or((a, b)))
+            .collect_vec();
+        let covered = ranges.iter().map(|range| range.len()).sum();
+        let gaps = ranges
+            .iter()
+            .tuple_windows()
+            .map(|(a, b)| Interval::new(a.end() + 1, b.start() - 1).expect("ranges are merged"))
+            .collect();
+        let (max_elves, busiest) = busiest(&intervals);
+        Self {
+            ranges,
+            covered,
+            gaps,
+            busiest,
+            max_elves,
+        }
+    }
+}
+
+fn busiest(intervals: &[Interval]) -> (usize, Vec<Interval>) {
+    // How many elves start minus how many stop being assigned at each section.
+    let mut deltas: BTreeMap<u64, i64> = BTreeMap::new();
+    for interval in intervals {
+        *deltas.entry(interval.start() as u64).or_default() += 1;
+        *deltas.entry(interval.end() as u64 + 1).or_default() -= 1;
+    }
+
+    let mut max = 0;
+    let mut busiest: Vec<Interval> = Vec::new();
+    let mut count = 0;
+    for ((position, delta), (next, _)) in deltas.iter().tuple_windows() {
+        count += delta;
+        let elves = count as usize;
+        if elves == 0 || elves < max {
+            continue;
+        }
+        if elves > max {
+            max = elves;
+            busiest.clear();
+        }
+        let range = Interval::new(*position as Section, (next - 1) as Section)
+            .expect("positions are sorted");
+        match busiest.last_mut() {
+            Some(last) if last.end() as u64 + 1 == *position => {
+                *last = last.union(range).expect("ranges touch");
+            }
+            _ => busiest.push(range),
+        }
+    }
+    (max, busiest)
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Covered sections: {}", self.covered)?;
+        writeln!(f, "Ranges: {}", self.ranges.iter().join(", "))?;
+        writeln!(f, "Gaps: {}", self.gaps.iter().join(", "))?;
+        write!(
+            f,
+            "Assigned to {} elves: {}",
+            self.max_elves,
+            self.busiest.iter().join(", ")
+        )
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt;
+
+use thiserror::Error;
+
+pub type Section = u32;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum IntervalError {
+    #[error("interval start {start} is after its end {end}")]
+    Reversed { start: Section, end: Section },
+}
+
+/// An inclusive range of sections, `start..=end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval {
+    start: Section,
+    end: Section,
+}
+
+impl Interval {
+    pub fn new(start: Section, end: Section) -> Result<Self, IntervalError> {
+        if start > end {
+            return Err(IntervalError::Reversed { start, end });
+        }
+        Ok(Self { start, end })
+    }
+
+    pub fn start(self) -> Section {
+        self.start
+    }
+
+    pub fn end(self) -> Section {
+        self.end
+    }
+
+    pub fn len(self) -> u64 {
+        (self.end - self.start) as u64 + 1
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn contains_point(self, section: Section) -> bool {
+        self.start <= section && section <= self.end
+    }
+
+    pub fn contains(self, other: Interval) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(self, other: Interval) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(self, other: Interval) -> Option<Interval> {
+        Interval::new(self.start.max(other.start), self.end.min(other.end)).ok()
+    }
+
+    /// The smallest interval covering both, if they overlap or touch so that
+    /// no section in between is left out.
+    pub fn union(self, other: Interval) -> Option<Interval> {
+        let (first, second) = if self <= other {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        if second.start as u64 > first.end as u64 + 1 {
+            return None;
+        }
+        Some(Interval {
+            start: first.start,
+            end: first.end.max(second.end),
+        })
+    }
+
+    /// The sections of `self` not in `other`, as at most two intervals in order.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn difference(self, other: Interval) -> Vec<Interval> {
+        if !self.overlaps(other) {
+            return vec![self];
+        }
+        let mut pieces = Vec::with_capacity(2);
+        if self.start < other.start {
+            pieces.push(Interval {
+                start: self.start,
+                end: other.start - 1,
+            });
+        }
+        if other.end < self.end {
+            pieces.push(Interval {
+                start: other.end + 1,
+                end: self.end,
+            });
+        }
+        pieces
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn interval() -> impl Strategy<Value = Interval> {
+        (0..64u32, 0..64u32).prop_map(|(a, b)| Interval::new(a.min(b), a.max(b)).unwrap())
+    }
+
+    #[test]
+    fn test_reversed() {
+        assert_eq!(
+            Interval::new(5, 4),
+            Err(IntervalError::Reversed { start: 5, end: 4 })
+        );
+        assert_eq!(Interval::new(4, 4).unwrap().len(), 1);
+    }
+
+    proptest! {
+        #[test]
+        fn intersection_laws(a in interval(), b in interval(), p in 0..64u32) {
+            prop_assert_eq!(a.intersection(b), b.intersection(a));
+            prop_assert_eq!(a.intersection(b).is_some(), a.overlaps(b));
+            let in_both = a.contains_point(p) && b.contains_point(p);
+            prop_assert_eq!(a.intersection(b).is_some_and(|i| i.contains_point(p)), in_both);
+            if let Some(i) = a.intersection(b) {
+                prop_assert!(a.contains(i) && b.contains(i));
+            }
+        }
+
+        #[test]
+        fn union_laws(a in interval(), b in interval(), p in 0..64u32) {
+            prop_assert_eq!(a.union(b), b.union(a));
+            match a.union(b) {
+                Some(u) => {
+                    prop_assert!(u.contains(a) && u.contains(b));
+                    prop_assert_eq!(u.contains_point(p), a.contains_point(p) || b.contains_point(p));
+                    let shared = a.intersection(b).map_or(0, Interval::len);
+                    prop_assert_eq!(a.len() + b.len(), u.len() + shared);
+                }
+                None => prop_assert!(!a.overlaps(b)),
+            }
+        }
+
+        #[test]
+        fn difference_laws(a in interval(), b in interval(), p in 0..64u32) {
+            let pieces = a.difference(b);
+            prop_assert!(pieces.len() <= 2);
+            prop_assert!(pieces.windows(2).all(|w| w[0].end < w[1].start));
+            let in_pieces = pieces.iter().any(|piece| piece.contains_point(p));
+            prop_assert_eq!(in_pieces, a.contains_point(p) && !b.contains_point(p));
+            let removed = a.intersection(b).map_or(0, Interval::len);
+            prop_assert_eq!(pieces.iter().map(|piece| piece.len()).sum::<u64>(), a.len() - removed);
+        }
+
+        #[test]
+        fn contains_laws(a in interval(), b in interval()) {
+            prop_assert_eq!(a.contains(b), a.intersection(b) == Some(b));
+            prop_assert!(a.contains(a));
+        }
+    }
+}
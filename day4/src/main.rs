@@ -1,68 +1,182 @@
-use anyhow::anyhow;
+mod coverage;
+mod interval;
+mod sweep;
 
-type ElfPair = (Elf, Elf);
-type Input = [ElfPair];
+use itertools::Itertools;
+use thiserror::Error;
+
+use coverage::Coverage;
+use interval::{Interval, IntervalError, Section};
+use sweep::PairReport;
+
+type Input = [ElfGroup];
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 struct Elf {
-    sections: (u32, u32),
+    sections: Interval,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+enum AssignmentError {
+    #[error("invalid assignment {0:?}, expected `start-end`")]
+    Malformed(String),
+    #[error(transparent)]
+    Interval(#[from] IntervalError),
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+enum ParseError {
+    #[error("line {line}: expected at least 2 comma-separated assignments, found {found}")]
+    FieldCount { line: usize, found: usize },
+    #[error("line {line}: {source}")]
+    Assignment {
+        line: usize,
+        source: AssignmentError,
+    },
+}
+
+fn parse_section(input: &str) -> Option<Section> {
+    if input.is_empty() || !input.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    input.parse().ok()
 }
 
 impl TryFrom<&str> for Elf {
-    type Error = anyhow::Error;
+    type Error = AssignmentError;
     fn try_from(input: &str) -> Result<Self, Self::Error> {
-        let sections = input
+        let (f, s) = input
             .split_once('-')
-            .map(|(f, s)| {
-                let f = f.parse::<u32>().unwrap();
-                let s = s.parse::<u32>().unwrap();
-                (f, s)
-            })
-            .ok_or(anyhow!("Failed to parse sections"))?;
+            .and_then(|(f, s)| Some((parse_section(f)?, parse_section(s)?)))
+            .ok_or_else(|| AssignmentError::Malformed(input.to_owned()))?;
+        let sections = Interval::new(f, s)?;
         Ok(Self { sections })
     }
 }
 
 impl Elf {
     pub fn contains(&self, other: &Elf) -> bool {
-        other.sections.0 >= self.sections.0 && other.sections.1 <= self.sections.1
+        self.sections.contains(other.sections)
     }
 
+    /// Symmetric: true when the two assignments share at least one section,
+    /// including when they only touch at an end.
     pub fn overlaps(&self, other: &Elf) -> bool {
-        other.sections.0 >= self.sections.0 && other.sections.0 <= self.sections.1
-            || other.sections.0 == self.sections.1
+        self.sections.overlaps(other.sections)
     }
 }
 
-fn parse(input: &str) -> anyhow::Result<Vec<ElfPair>> {
-    input
+#[derive(Debug, PartialEq, Eq)]
+struct ElfGroup {
+    elves: Vec<Elf>,
+}
+
+impl ElfGroup {
+    pub fn any_overlap(&self) -> bool {
+        // In start order, an elf overlaps an earlier one exactly when it
+        // overlaps the earlier one reaching furthest.
+        let mut furthest: Option<&Elf> = None;
+        self.elves.iter().sorted().any(|elf| {
+            let overlaps = furthest.is_some_and(|f| f.overlaps(elf));
+            if furthest.is_none_or(|f| elf.sections.end() > f.sections.end()) {
+                furthest = Some(elf);
+            }
+            overlaps
+        })
+    }
+
+    /// True when a single section is shared by every assignment, which for
+    /// intervals is the same as every pair overlapping.
+    pub fn all_overlap(&self) -> bool {
+        self.elves
+            .iter()
+            .map(|elf| Some(elf.sections))
+            .reduce(|a, b| a?.intersection(b?))
+            .flatten()
+            .is_some()
+    }
+
+    pub fn has_container(&self) -> bool {
+        self.elves
+            .iter()
+            .any(|elf| self.elves.iter().all(|other| elf.contains(other)))
+    }
+}
+
+fn parse_line(line: usize, input: &str) -> Result<ElfGroup, ParseError> {
+    let fields: Vec<&str> = input.split(',').collect();
+    if fields.len() < 2 {
+        return Err(ParseError::FieldCount {
+            line,
+            found: fields.len(),
+        });
+    }
+    let elves = fields
+        .into_iter()
+        .map(|text| Elf::try_from(text).map_err(|source| ParseError::Assignment { line, source }))
+        .collect::<Result<_, _>>()?;
+    Ok(ElfGroup { elves })
+}
+
+fn parse(input: &str) -> anyhow::Result<Vec<ElfGroup>> {
+    let groups = input
         .lines()
-        .map(|line| line.split_once(',').ok_or(anyhow!("Split failed!")))
-        .map(|line| line.map(|(a, b)| (Elf::try_from(a).unwrap(), Elf::try_from(b).unwrap())))
-        .collect()
+        .enumerate()
+        .map(|(index, line)| parse_line(index + 1, line))
+        .collect::<Result<_, _>>()?;
+    Ok(groups)
 }
 
 fn part1(input: &Input) -> anyhow::Result<u32> {
-    let res = input
-        .iter()
-        .filter(|(elf_a, elf_b)| elf_a.contains(elf_b) || elf_b.contains(elf_a))
-        .count();
+    let res = input.iter().filter(|group| group.has_container()).count();
     Ok(res as u32)
 }
 
 fn part2(input: &Input) -> anyhow::Result<u32> {
-    let res = input
-        .iter()
-        .filter(|(elf_a, elf_b)| elf_a.overlaps(elf_b) || elf_b.overlaps(elf_a))
-        .count();
+    let res = input.iter().filter(|group| group.any_overlap()).count();
     Ok(res as u32)
 }
 
+fn all_sections(input: &Input) -> Vec<Interval> {
+    input
+        .iter()
+        .flat_map(|group| group.elves.iter().map(|elf| elf.sections))
+        .collect()
+}
+
+fn coverage(input: &Input) -> Coverage {
+    Coverage::new(all_sections(input))
+}
+
+fn all_pairs(input: &Input) -> PairReport {
+    PairReport::new(&all_sections(input))
+}
+
 fn main() -> anyhow::Result<()> {
     let input = include_str!("input.txt");
     let input = parse(input)?;
     println!("Part 1: {}", part1(&input)?);
     println!("Part 2: {}", part2(&input)?);
+    if std::env::args().any(|arg| arg == "--coverage") {
+        println!("\n{}", coverage(&input));
+    }
+    if std::env::args().any(|arg| arg == "--all-pairs") {
+        let report = all_pairs(&input);
+        println!("\nOverlapping pairs: {}", report.overlapping.len());
+        println!("Containing pairs: {}", report.containing.len());
+    }
+    if std::env::args().any(|arg| arg == "--groups") {
+        let count = |f: fn(&ElfGroup) -> bool| input.iter().filter(|g| f(g)).count();
+        println!(
+            "\nGroups with any overlap: {}",
+            count(ElfGroup::any_overlap)
+        );
+        println!("Groups sharing a section: {}", count(ElfGroup::all_overlap));
+        println!(
+            "Groups with a container: {}",
+            count(ElfGroup::has_container)
+        );
+    }
     Ok(())
 }
 
@@ -82,4 +196,157 @@ mod test {
         let res = part2(&parse(INPUT).unwrap()).unwrap();
         assert_eq!(res, 4)
     }
+
+    #[test]
+    fn test_overlaps_exhaustive() {
+        use std::collections::BTreeSet;
+
+        const MAX: u32 = 8;
+        let elves: Vec<Elf> = (0..=MAX)
+            .flat_map(|start| (start..=MAX).map(move |end| (start, end)))
+            .map(|(start, end)| Elf {
+                sections: Interval::new(start, end).unwrap(),
+            })
+            .collect();
+        let sections =
+            |elf: &Elf| -> BTreeSet<u32> { (elf.sections.start()..=elf.sections.end()).collect() };
+
+        for a in &elves {
+            for b in &elves {
+                let (set_a, set_b) = (sections(a), sections(b));
+                assert_eq!(
+                    a.overlaps(b),
+                    !set_a.is_disjoint(&set_b),
+                    "overlaps({:?}, {:?})",
+                    a,
+                    b
+                );
+                assert_eq!(a.overlaps(b), b.overlaps(a));
+                assert_eq!(a.contains(b), set_b.is_subset(&set_a));
+            }
+        }
+
+        let elf = |s: &str| Elf::try_from(s).unwrap();
+        assert!(elf("2-4").overlaps(&elf("4-6")));
+        assert!(elf("4-6").overlaps(&elf("2-4")));
+        assert!(!elf("2-3").overlaps(&elf("4-5")));
+        assert!(elf("2-8").overlaps(&elf("3-7")) && elf("3-7").overlaps(&elf("2-8")));
+        assert!(elf("3-3").overlaps(&elf("3-3")));
+    }
+
+    #[test]
+    fn test_coverage() {
+        let coverage = coverage(&parse(INPUT).unwrap());
+        let interval = |s: u32, e: u32| Interval::new(s, e).unwrap();
+        assert_eq!(coverage.ranges, vec![interval(2, 9)]);
+        assert_eq!(coverage.covered, 8);
+        assert!(coverage.gaps.is_empty());
+        assert_eq!(coverage.max_elves, 8);
+        assert_eq!(coverage.busiest, vec![interval(6, 6)]);
+
+        let coverage = Coverage::new([
+            interval(1, 2),
+            interval(3, 4),
+            interval(8, 9),
+            interval(6, 6),
+        ]);
+        assert_eq!(
+            coverage.ranges,
+            vec![interval(1, 4), interval(6, 6), interval(8, 9)]
+        );
+        assert_eq!(coverage.covered, 7);
+        assert_eq!(coverage.gaps, vec![interval(5, 5), interval(7, 7)]);
+        assert_eq!(coverage.max_elves, 1);
+        assert_eq!(coverage.busiest, coverage.ranges);
+        assert_eq!(
+            coverage.to_string(),
+            "Covered sections: 7\nRanges: 1-4, 6-6, 8-9\nGaps: 5-5, 7-7\nAssigned to 1 elves: 1-4, 6-6, 8-9"
+        );
+    }
+
+    #[test]
+    fn test_all_pairs() {
+        let report = all_pairs(&parse("2-4,6-8\n3-6,7-7").unwrap());
+        assert_eq!(report.overlapping, vec![(0, 2), (1, 2), (1, 3)]);
+        assert_eq!(report.containing, vec![(1, 3)]);
+
+        let input = parse(INPUT).unwrap();
+        let report = all_pairs(&input);
+        let same_line = |(i, j): &(usize, usize)| i / 2 == j / 2;
+        let containing_on_line = report.containing.iter().filter(|p| same_line(p)).count();
+        let overlapping_on_line = report.overlapping.iter().filter(|p| same_line(p)).count();
+        assert_eq!(containing_on_line as u32, part1(&input).unwrap());
+        assert_eq!(overlapping_on_line as u32, part2(&input).unwrap());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |input| parse(input).unwrap_err().downcast::<ParseError>().unwrap();
+        let malformed = |line, text: &str| ParseError::Assignment {
+            line,
+            source: AssignmentError::Malformed(text.to_owned()),
+        };
+        assert_eq!(err("2-4"), ParseError::FieldCount { line: 1, found: 1 });
+        assert_eq!(err("2-4,6-8x"), malformed(1, "6-8x"));
+        assert_eq!(err("2-4,6-8-9"), malformed(1, "6-8-9"));
+        assert_eq!(err("2-4, 6-8"), malformed(1, " 6-8"));
+        assert_eq!(err("2-4,+6-8"), malformed(1, "+6-8"));
+        assert_eq!(
+            err("1-1,2-2\n\n3-3,4-4"),
+            ParseError::FieldCount { line: 2, found: 1 }
+        );
+        assert_eq!(
+            err("2-4,6-8\n2-3,4-5\n5-7,9-7"),
+            ParseError::Assignment {
+                line: 3,
+                source: AssignmentError::Interval(IntervalError::Reversed { start: 9, end: 7 })
+            }
+        );
+        assert_eq!(
+            err("2-4,9-7").to_string(),
+            "line 1: interval start 9 is after its end 7"
+        );
+    }
+
+    #[test]
+    fn test_groups() {
+        let input =
+            parse("2-4,6-8,9-9\n2-4,6-8,4-6\n3-5,4-6,5-7\n1-9,2-3,8-9\n5-5,5-5,5-5").unwrap();
+        let any: Vec<bool> = input.iter().map(ElfGroup::any_overlap).collect();
+        let all: Vec<bool> = input.iter().map(ElfGroup::all_overlap).collect();
+        let container: Vec<bool> = input.iter().map(ElfGroup::has_container).collect();
+        assert_eq!(any, vec![false, true, true, true, true]);
+        assert_eq!(all, vec![false, false, true, false, true]);
+        assert_eq!(container, vec![false, false, false, true, true]);
+
+        let group = parse("1-10,2-3,5-6,8-9").unwrap().remove(0);
+        assert!(group.any_overlap() && !group.all_overlap() && group.has_container());
+        let group = parse("1-3,5-9,6-6,10-12").unwrap().remove(0);
+        assert!(group.any_overlap() && !group.all_overlap() && !group.has_container());
+    }
+
+    #[test]
+    fn test_group_predicates_match_pairwise() {
+        let elves: Vec<Elf> = [(1, 3), (2, 5), (4, 4), (6, 8), (1, 8)]
+            .into_iter()
+            .map(|(s, e)| Elf {
+                sections: Interval::new(s, e).unwrap(),
+            })
+            .collect();
+        for size in 2..=elves.len() {
+            for subset in elves.iter().combinations(size) {
+                let group = ElfGroup {
+                    elves: subset
+                        .iter()
+                        .map(|elf| Elf {
+                            sections: elf.sections,
+                        })
+                        .collect(),
+                };
+                let pairs = || subset.iter().combinations(2);
+                assert_eq!(group.any_overlap(), pairs().any(|p| p[0].overlaps(p[1])));
+                assert_eq!(group.all_overlap(), pairs().all(|p| p[0].overlaps(p[1])));
+            }
+        }
+    }
 }
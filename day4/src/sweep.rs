@@ -0,0 +1,77 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use itertools::Itertools;
+
+use crate::interval::{Interval, Section};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PairReport {
+    /// Index pairs `(i, j)` with `i < j` whose intervals share a section.
+    pub overlapping: Vec<(usize, usize)>,
+    /// The subset of `overlapping` where one interval contains the other.
+    pub containing: Vec<(usize, usize)>,
+}
+
+impl PairReport {
+    pub fn new(intervals: &[Interval]) -> Self {
+        let overlapping = overlapping_pairs(intervals);
+        let containing = overlapping
+            .iter()
+            .copied()
+            .filter(|(i, j)| {
+                let (a, b) = (intervals[*i], intervals[*j]);
+                a.contains(b) || b.contains(a)
+            })
+            .collect();
+        Self {
+            overlapping,
+            containing,
+        }
+    }
+}
+
+/// Sweeps the intervals in order of their start, keeping the ones still open
+/// in a heap keyed by their end, so each overlapping pair is found once
+/// without comparing every pair.
+pub fn overlapping_pairs(intervals: &[Interval]) -> Vec<(usize, usize)> {
+    let mut active: BinaryHeap<Reverse<(Section, usize)>> = BinaryHeap::new();
+    let mut pairs = Vec::new();
+    for i in (0..intervals.len()).sorted_by_key(|i| intervals[*i].start()) {
+        let interval = intervals[i];
+        while let Some(Reverse((end, _))) = active.peek() {
+            if *end >= interval.start() {
+                break;
+            }
+            active.pop();
+        }
+        pairs.extend(active.iter().map(|Reverse((_, j))| (i.min(*j), i.max(*j))));
+        active.push(Reverse((interval.end(), i)));
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn brute_force(intervals: &[Interval]) -> Vec<(usize, usize)> {
+        (0..intervals.len())
+            .combinations(2)
+            .filter(|pair| intervals[pair[0]].overlaps(intervals[pair[1]]))
+            .map(|pair| (pair[0], pair[1]))
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn matches_brute_force(bounds in prop::collection::vec((0..50u32, 0..50u32), 0..40)) {
+            let intervals = bounds
+                .into_iter()
+                .map(|(a, b)| Interval::new(a.min(b), a.max(b)).unwrap())
+                .collect_vec();
+            prop_assert_eq!(overlapping_pairs(&intervals), brute_force(&intervals));
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use std::num::NonZeroUsize;
+
+use thiserror::Error;
+
+use crate::{Instruction, Stacks};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MoveError {
+    #[error("there is no stack {0}")]
+    NoSuchStack(u32),
+    #[error("stack {stack} holds only {available} crates")]
+    NotEnoughCrates { stack: u32, available: usize },
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("instruction {index} ({instruction}) on stacks of sizes {sizes:?}: {source}")]
+pub struct ExecutionError {
+    pub index: usize,
+    pub instruction: Instruction,
+    pub sizes: Vec<usize>,
+    pub source: MoveError,
+}
+
+/// A crane model, described by how it splits a move into lifts.
+pub trait CrateMover {
+    /// Sizes of the blocks moved for an `amount`-crate instruction, in the
+    /// order they are lifted. Each block keeps its order when set down.
+    fn lifts(&self, amount: usize) -> Vec<usize>;
+
+    /// Where the crate `position` places from the top of a moved block of
+    /// `amount` crates was in the source stack before the move, counted from
+    /// its top.
+    fn source_position(&self, amount: usize, position: usize) -> usize {
+        let mut above = 0;
+        let mut start = amount;
+        for lift in self.lifts(amount).into_iter().rev() {
+            start -= lift;
+            if position < above + lift {
+                return start + position - above;
+            }
+            above += lift;
+        }
+        panic!("position {position} is outside a move of {amount} crates")
+    }
+}
+
+/// Moves crates one at a time, reversing their order.
+pub struct CrateMover9000;
+
+/// Moves all crates of an instruction at once, keeping their order.
+pub struct CrateMover9001;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("a crane has to lift at least one crate at a time")]
+pub struct ZeroCapacity;
+
+/// Moves at most `capacity` crates at once, taking as many as it can per lift.
+pub struct CappedCrateMover {
+    capacity: NonZeroUsize,
+}
+
+impl CappedCrateMover {
+    pub fn new(capacity: usize) -> Result<Self, ZeroCapacity> {
+        let capacity = NonZeroUsize::new(capacity).ok_or(ZeroCapacity)?;
+        Ok(Self { capacity })
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity.get()
+    }
+}
+
+impl CrateMover for CrateMover9000 {
+    fn lifts(&self, amount: usize) -> Vec<usize> {
+        vec![1; amount]
+    }
+
+    fn source_position(&self, amount: usize, position: usize) -> usize {
+        amount - 1 - position
+    }
+}
+
+impl CrateMover for CrateMover9001 {
+    fn lifts(&self, amount: usize) -> Vec<usize> {
+        vec![amount]
+    }
+
+    fn source_position(&self, _amount: usize, position: usize) -> usize {
+        position
+    }
+}
+
+impl CrateMover for CappedCrateMover {
+    fn lifts(&self, amount: usize) -> Vec<usize> {
+        let capacity = self.capacity();
+        (0..amount)
+            .step_by(capacity)
+            .map(|lifted| capacity.min(amount - lifted))
+            .collect()
+    }
+
+    /// The top of the moved block is the last lift, which is only partly full
+    /// when `amount` is not a multiple of the capacity. Below it come full
+    /// lifts, each taken from further down the source stack.
+    fn source_position(&self, amount: usize, position: usize) -> usize {
+        assert!(
+            position < amount,
+            "position {position} is outside a move of {amount} crates"
+        );
+        let capacity = self.capacity();
+        let last = match amount % capacity {
+            0 => capacity,
+            partial => partial,
+        };
+        if position < last {
+            return amount - last + position;
+        }
+        let below = position - last;
+        let start = amount - last - (below / capacity + 1) * capacity;
+        start + below % capacity
+    }
+}
+
+/// Checks `inst` against stacks whose sizes are given by `size`.
+pub fn check(size: impl Fn(u32) -> Option<usize>, inst: &Instruction) -> Result<(), MoveError> {
+    let available = size(inst.from).ok_or(MoveError::NoSuchStack(inst.from + 1))?;
+    size(inst.to).ok_or(MoveError::NoSuchStack(inst.to + 1))?;
+    if available < inst.amount as usize {
+        return Err(MoveError::NotEnoughCrates {
+            stack: inst.from + 1,
+            available,
+        });
+    }
+    Ok(())
+}
+
+fn sizes<T>(stacks: &[Vec<T>]) -> impl Fn(u32) -> Option<usize> + '_ {
+    |index| stacks.get(index as usize).map(Vec::len)
+}
+
+/// Moving crates onto the stack they came from leaves it unchanged, since
+/// every lift is set back down where it was picked up.
+fn move_crates<T>(stacks: &mut [Vec<T>], from: usize, to: usize, lifts: &[usize]) {
+    if from == to {
+        return;
+    }
+    for &lift in lifts {
+        let src = &mut stacks[from];
+        let block: Vec<T> = src.drain(src.len() - lift..).collect();
+        stacks[to].extend(block);
+    }
+}
+
+/// Moves the crates of `inst` in the given lifts.
+pub fn apply<T>(
+    stacks: &mut [Vec<T>],
+    index: usize,
+    inst: &Instruction,
+    lifts: &[usize],
+) -> Result<(), ExecutionError> {
+    check(sizes(stacks), inst).map_err(|source| ExecutionError {
+        index,
+        instruction: *inst,
+        sizes: stacks.iter().map(Vec::len).collect(),
+        source,
+    })?;
+    move_crates(stacks, inst.from as usize, inst.to as usize, lifts);
+    Ok(())
+}
+
+/// Puts the crates moved by `inst` in the given lifts back where they came
+/// from, last lift first.
+pub fn revert<T>(
+    stacks: &mut [Vec<T>],
+    index: usize,
+    inst: &Instruction,
+    lifts: &[usize],
+) -> Result<(), ExecutionError> {
+    let back = Instruction {
+        amount: inst.amount,
+        from: inst.to,
+        to: inst.from,
+    };
+    check(sizes(stacks), &back).map_err(|source| ExecutionError {
+        index,
+        instruction: *inst,
+        sizes: stacks.iter().map(Vec::len).collect(),
+        source,
+    })?;
+    let lifts: Vec<usize> = lifts.iter().rev().copied().collect();
+    move_crates(stacks, back.from as usize, back.to as usize, &lifts);
+    Ok(())
+}
+
+pub fn execute(
+    mover: &dyn CrateMover,
+    stacks: &mut Stacks,
+    index: usize,
+    inst: &Instruction,
+) -> Result<(), ExecutionError> {
+    apply(
+        &mut stacks.0,
+        index,
+        inst,
+        &mover.lifts(inst.amount as usize),
+    )
+}
+
+pub fn simulate(
+    mover: &dyn CrateMover,
+    stacks: &mut Stacks,
+    instructions: &[Instruction],
+) -> Result<(), ExecutionError> {
+    for (index, inst) in instructions.iter().enumerate() {
+        execute(mover, stacks, index, inst)?;
+    }
+    Ok(())
+}
+
+/// Undoes the instructions from last to first, turning the final stacks back
+/// into the ones the instructions started from.
+pub fn simulate_backwards(
+    mover: &dyn CrateMover,
+    stacks: &mut Stacks,
+    instructions: &[Instruction],
+) -> Result<(), ExecutionError> {
+    for (index, inst) in instructions.iter().enumerate().rev() {
+        revert(
+            &mut stacks.0,
+            index,
+            inst,
+            &mover.lifts(inst.amount as usize),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Crate;
+
+    #[test]
+    fn test_capped_crane() {
+        assert!(matches!(CappedCrateMover::new(0), Err(ZeroCapacity)));
+        let crane = CappedCrateMover::new(2).unwrap();
+        assert_eq!(crane.lifts(5), vec![2, 2, 1]);
+        assert_eq!(crane.lifts(4), vec![2, 2]);
+
+        let mut stacks = Stacks(vec!["ABCDE".chars().map(Crate).collect(), vec![]]);
+        let inst = Instruction {
+            amount: 5,
+            from: 0,
+            to: 1,
+        };
+        execute(&crane, &mut stacks, 0, &inst).unwrap();
+        let moved: String = stacks.0[1].iter().map(|c| c.0).collect();
+        assert_eq!(moved, "DEBCA");
+        let positions: Vec<usize> = (0..5).map(|p| crane.source_position(5, p)).collect();
+        assert_eq!(positions, vec![4, 2, 3, 0, 1]);
+    }
+
+    #[test]
+    fn test_capped_source_position_of_large_move() {
+        let crane = CappedCrateMover::new(2).unwrap();
+        let amount = (1 << 40) + 1;
+        assert_eq!(crane.source_position(amount, 0), amount - 1);
+        assert_eq!(crane.source_position(amount, 1), amount - 3);
+        assert_eq!(crane.source_position(amount, 2), amount - 2);
+        assert_eq!(crane.source_position(amount, amount - 1), 1);
+        let crane = CappedCrateMover::new(1 << 20).unwrap();
+        assert_eq!(crane.source_position(amount << 1, 0), (amount << 1) - 2);
+    }
+
+    #[test]
+    fn test_invalid_instructions() {
+        let stacks = Stacks(vec![vec![Crate('A'), Crate('B')], vec![]]);
+        let run = |amount, from, to| {
+            let mut stacks = stacks.clone();
+            let inst = Instruction { amount, from, to };
+            simulate(&CrateMover9001, &mut stacks, &[inst, inst]).map(|_| stacks)
+        };
+        let err = run(2, 0, 1).unwrap_err();
+        assert_eq!(err.index, 1);
+        assert_eq!(err.sizes, vec![0, 2]);
+        assert_eq!(
+            err.source,
+            MoveError::NotEnoughCrates {
+                stack: 1,
+                available: 0
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "instruction 1 (move 2 from 1 to 2) on stacks of sizes [0, 2]: stack 1 holds only 0 crates"
+        );
+        assert_eq!(run(1, 0, 2).unwrap_err().source, MoveError::NoSuchStack(3));
+        assert_eq!(run(1, 5, 0).unwrap_err().source, MoveError::NoSuchStack(6));
+        assert_eq!(run(2, 0, 0).unwrap(), stacks);
+        assert!(run(3, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_simulate_backwards() {
+        let (initial, instructions) = crate::parse(include_str!("input_test.txt")).unwrap();
+        let movers: [&dyn CrateMover; 3] = [
+            &CrateMover9000,
+            &CrateMover9001,
+            &CappedCrateMover::new(2).unwrap(),
+        ];
+        for mover in movers {
+            let mut stacks = initial.clone();
+            simulate(mover, &mut stacks, &instructions).unwrap();
+            simulate_backwards(mover, &mut stacks, &instructions).unwrap();
+            assert_eq!(stacks, initial);
+        }
+
+        let mut stacks = initial.clone();
+        let err = simulate_backwards(&CrateMover9000, &mut stacks, &instructions).unwrap_err();
+        assert_eq!(err.index, 1);
+        assert_eq!(
+            err.source,
+            MoveError::NotEnoughCrates {
+                stack: 3,
+                available: 1
+            }
+        );
+    }
+}
@@ -1,45 +1,97 @@
-use anyhow::Result;
+use std::{fmt, num::NonZeroUsize};
+
+use anyhow::{anyhow, Result};
+use crane::{
+    simulate, simulate_backwards, CappedCrateMover, CrateMover, CrateMover9000, CrateMover9001,
+};
+use itertools::Itertools;
+use provenance::Provenance;
+use render::simulate_steps;
+use trace::traced_tops;
 
 use nom::{
-    branch::alt,
-    bytes::complete::{tag, take_until},
-    character::complete::{self, anychar, digit1, line_ending},
-    combinator::{map, map_res, value},
-    multi::separated_list1,
-    sequence::{delimited, terminated, tuple},
+    bytes::complete::tag,
+    character::complete::{digit1, line_ending},
+    combinator::{map, map_res},
+    multi::separated_list0,
+    sequence::tuple,
     IResult,
 };
 
+mod crane;
+mod provenance;
+mod render;
+mod repl;
+mod simulator;
+mod trace;
+
 type Input = (Stacks, Instructions);
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Crate(char);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Stacks(Vec<Vec<Crate>>);
 
 impl Stacks {
-    fn from_rows(rows: Vec<Vec<Option<Crate>>>) -> Result<Self> {
-        if rows.is_empty() {
-            return Err(anyhow::anyhow!("No rows to be found!"));
+    /// Stacks up crate rows given top to bottom, starting on the first line.
+    fn from_rows(rows: &[Vec<Option<Crate>>], count: usize) -> Result<Self> {
+        let mut stacks = vec![Vec::new(); count];
+        for (index, row) in rows.iter().enumerate().rev() {
+            let level = rows.len() - 1 - index;
+            for (stack, c) in row.iter().enumerate() {
+                let Some(c) = c else { continue };
+                if stacks[stack].len() < level {
+                    return Err(anyhow!(
+                        "line {}: crate [{}] in stack {} floats above a gap",
+                        index + 1,
+                        c.0,
+                        stack + 1
+                    ));
+                }
+                stacks[stack].push(*c);
+            }
         }
-
-        let len = rows[0].len();
-        let mut iter: Vec<_> = rows.into_iter().map(|inner| inner.into_iter()).collect();
-        let stacks = (0..len)
-            .map(|_| {
-                iter.iter_mut()
-                    .rev()
-                    .filter_map(|inner| inner.next())
-                    .flatten()
-                    .collect()
-            })
-            .collect();
         Ok(Self(stacks))
     }
+
+    fn heights(&self) -> Vec<usize> {
+        self.0.iter().map(Vec::len).collect()
+    }
+
+    /// The crates of stack `index`, bottom first.
+    fn stack(&self, index: usize) -> Option<&[Crate]> {
+        self.0.get(index).map(Vec::as_slice)
+    }
+
+    fn tops(&self) -> Tops {
+        Tops(self.0.iter().map(|stack| stack.last().copied()).collect())
+    }
+}
+
+/// Stands in for empty stacks in the answer unless another placeholder is given.
+const EMPTY: char = ' ';
+
+/// The crate on top of each stack, or `None` where the stack is empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tops(Vec<Option<Crate>>);
+
+impl Tops {
+    fn answer(&self, placeholder: char) -> String {
+        self.0
+            .iter()
+            .map(|top| top.map_or(placeholder, |c| c.0))
+            .collect()
+    }
+}
+
+impl fmt::Display for Tops {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.answer(EMPTY))
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Instruction {
     pub amount: u32,
     pub from: u32,
@@ -51,123 +103,380 @@ type Instructions = Vec<Instruction>;
 type ParseResult<'a, T> = IResult<&'a str, T>;
 type ParseInput<'a> = &'a str;
 
-fn some_crate(input: ParseInput) -> ParseResult<Option<Crate>> {
-    let p = delimited(complete::char('['), anychar, complete::char(']'));
-    map(p, |c| Some(Crate(c)))(input)
-}
-
-fn none_crate(input: ParseInput) -> ParseResult<Option<Crate>> {
-    value(None, tag("   "))(input)
+/// Reads the stack labels, which have to count up from 1 with each one
+/// starting under the crate letter of its column, one past the left bracket.
+fn parse_labels(row: &str, line: usize) -> Result<usize> {
+    let mut count = 0;
+    let mut offset = 0;
+    for label in row.split(' ') {
+        if !label.is_empty() {
+            count += 1;
+            if label != count.to_string() {
+                return Err(anyhow!(
+                    "line {line}: expected stack label {count}, found {label:?}"
+                ));
+            }
+            if offset != 4 * (count - 1) + 1 {
+                return Err(anyhow!(
+                    "line {line}: stack label {label} is not under its column"
+                ));
+            }
+        }
+        offset += label.len() + 1;
+    }
+    if count == 0 {
+        return Err(anyhow!("line {line}: expected stack labels"));
+    }
+    Ok(count)
 }
 
-fn parse_stacks(input: ParseInput) -> ParseResult<Vec<Vec<Option<Crate>>>> {
-    let crate_rows = separated_list1(complete::char(' '), alt((some_crate, none_crate)));
-    let all_rows = separated_list1(line_ending, crate_rows);
-    terminated(all_rows, complete::char('\n'))(input)
+/// Reads a row of `count` crates or gaps, where trailing gaps may be left out.
+fn parse_crate_row(row: &str, count: usize, line: usize) -> Result<Vec<Option<Crate>>> {
+    let width = 4 * count - 1;
+    let mut cells: Vec<char> = row.trim_end().chars().collect();
+    if cells.len() > width {
+        return Err(anyhow!(
+            "line {line}: row is wider than the {count} labelled stacks"
+        ));
+    }
+    cells.resize(width, ' ');
+    cells
+        .chunks(4)
+        .enumerate()
+        .map(|(stack, cell)| match cell {
+            ['[', c, ']'] | ['[', c, ']', ' '] => Ok(Some(Crate(*c))),
+            [' ', ' ', ' '] | [' ', ' ', ' ', ' '] => Ok(None),
+            _ => Err(anyhow!(
+                "line {line}: expected a crate or gap in stack {}, found {:?}",
+                stack + 1,
+                cell.iter().collect::<String>()
+            )),
+        })
+        .collect()
 }
 
-fn skip_line(input: ParseInput) -> ParseResult<()> {
-    value((), terminated(take_until("\n"), line_ending))(input)
+/// Reads the drawing of crates above a row of stack labels.
+fn parse_stacks(drawing: &[&str]) -> Result<Stacks> {
+    let (labels, rows) = drawing
+        .split_last()
+        .ok_or(anyhow!("line 1: expected a drawing of the stacks"))?;
+    let count = parse_labels(labels, drawing.len())?;
+    let rows: Vec<_> = rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| parse_crate_row(row, count, index + 1))
+        .try_collect()?;
+    Stacks::from_rows(&rows, count)
 }
 
 fn parse_u32(input: &str) -> IResult<&str, u32> {
     map_res(digit1, str::parse)(input)
 }
 
-fn parse_instructions(input: ParseInput) -> ParseResult<Instructions> {
-    let instructions = map(
+fn parse_stack_number(input: ParseInput) -> ParseResult<u32> {
+    map_res(parse_u32, |number| {
+        number.checked_sub(1).ok_or("stacks are numbered from 1")
+    })(input)
+}
+
+fn parse_instruction(input: ParseInput) -> ParseResult<Instruction> {
+    map(
         tuple((
             tag("move "),
             parse_u32,
             tag(" from "),
-            parse_u32,
+            parse_stack_number,
             tag(" to "),
-            parse_u32,
+            parse_stack_number,
         )),
-        |(_, amount, _, from, _, to)| Instruction {
-            amount,
-            from: from - 1,
-            to: to - 1,
-        },
-    );
-    separated_list1(line_ending, instructions)(input)
+        |(_, amount, _, from, _, to)| Instruction { amount, from, to },
+    )(input)
+}
+
+fn parse_instructions(input: ParseInput) -> ParseResult<Instructions> {
+    separated_list0(line_ending, parse_instruction)(input)
 }
 
 fn parse(input: &str) -> Result<Input> {
-    let (remaining, rows) = parse_stacks(input).unwrap();
-    let stacks = Stacks::from_rows(rows)?;
+    let mut drawing = Vec::new();
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            break;
+        }
+        drawing.push(line.trim_end_matches(['\r', '\n']));
+        offset += line.len();
+    }
+    let stacks = parse_stacks(&drawing)?;
 
-    let (remaining, _) = skip_line(remaining).unwrap();
-    let (remaining, _) = skip_line(remaining).unwrap();
-    let (_, instructions) = parse_instructions(remaining).unwrap();
+    let remaining = input[offset..].trim_start();
+    let (remaining, instructions) = parse_instructions(remaining).map_err(|_| {
+        anyhow!(
+            "line {}: invalid instruction",
+            line_number(input, remaining)
+        )
+    })?;
+    let remaining = remaining.trim_start();
+    if !remaining.is_empty() {
+        let text = remaining.lines().next().unwrap_or_default();
+        return Err(anyhow!(
+            "line {}: invalid instruction {text:?}",
+            line_number(input, remaining)
+        ));
+    }
     Ok((stacks, instructions))
 }
 
-fn part1(input: Input) -> Result<String> {
-    let (mut stacks, instructions) = input;
-    for inst in instructions {
-        for _ in 0..inst.amount {
-            let src = stacks.0[inst.from as usize]
-                .pop()
-                .ok_or(anyhow::anyhow!("Failed to pop from source stack!"))?;
-            stacks.0[inst.to as usize].push(src);
-        }
-    }
-    let res: String = stacks
-        .0
-        .into_iter()
-        .map(|stack| stack.last().map(|c| c.0).unwrap())
-        .collect();
+/// The 1-based line of `input` on which its suffix `rest` starts.
+fn line_number(input: &str, rest: &str) -> usize {
+    input[..input.len() - rest.len()].matches('\n').count() + 1
+}
 
-    Ok(res)
+/// Writes stacks and instructions back out in the puzzle input format.
+fn serialize(stacks: &Stacks, instructions: &[Instruction]) -> String {
+    format!("{stacks}\n\n{}", instructions.iter().join("\n"))
 }
 
-fn part2(input: Input) -> Result<String> {
+fn run(mover: &dyn CrateMover, input: Input) -> Result<Tops> {
     let (mut stacks, instructions) = input;
-    for inst in instructions {
-        let from = inst.from;
-        let to = inst.to;
-        let (src, dst) = if from < to {
-            let (l, r) = stacks.0.split_at_mut(to as usize);
-            (&mut l[from as usize], &mut r[0])
-        } else {
-            let (l, r) = stacks.0.split_at_mut(from as usize);
-            (&mut r[0], &mut l[to as usize])
-        };
-        let drain_amount = src.len() - (inst.amount as usize);
-        dst.extend(src.drain(drain_amount..));
-    }
-    let res: String = stacks
-        .0
-        .into_iter()
-        .map(|stack| stack.last().map(|c| c.0).unwrap())
-        .collect();
-
-    Ok(res)
+    simulate(mover, &mut stacks, &instructions)?;
+    Ok(stacks.tops())
+}
+
+fn part1(input: Input) -> Result<Tops> {
+    run(&CrateMover9000, input)
+}
+
+fn part2(input: Input) -> Result<Tops> {
+    run(&CrateMover9001, input)
 }
 
 fn main() -> Result<()> {
     let input = include_str!("input.txt");
     let input = parse(input)?;
-    println!("Part 1: {}", part1(input.clone())?);
-    println!("Part 2: {}", part2(input)?);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let placeholder = args
+        .iter()
+        .find_map(|arg| arg.strip_prefix("--placeholder="))
+        .and_then(|placeholder| placeholder.chars().next())
+        .unwrap_or(EMPTY);
+    println!("Part 1: {}", part1(input.clone())?.answer(placeholder));
+    println!("Part 2: {}", part2(input.clone())?.answer(placeholder));
+    for arg in args {
+        if let Some(capacity) = arg.strip_prefix("--capacity=") {
+            let crane = CappedCrateMover::new(capacity.parse()?)?;
+            println!(
+                "Capacity {}: {}",
+                crane.capacity(),
+                run(&crane, input.clone())?.answer(placeholder)
+            );
+        }
+        if let Some(every) = arg.strip_prefix("--steps") {
+            let every = match every {
+                "" => NonZeroUsize::MIN,
+                _ => {
+                    let every = every.strip_prefix('=').ok_or(anyhow!(
+                        "unknown argument {arg:?}, expected --steps or --steps=N"
+                    ))?;
+                    NonZeroUsize::new(every.parse()?)
+                        .ok_or(anyhow!("--steps=N needs N to be at least 1"))?
+                }
+            };
+            let (mut stacks, instructions) = input.clone();
+            let mut out = std::io::stdout().lock();
+            simulate_steps(&CrateMover9001, &mut stacks, &instructions, every, &mut out)?;
+        }
+        if let Some(step) = arg.strip_prefix("--save=") {
+            let (mut stacks, instructions) = input.clone();
+            let step: usize = step.parse()?;
+            if step > instructions.len() {
+                return Err(anyhow!(
+                    "cannot save after step {step}, there are only {} instructions",
+                    instructions.len()
+                ));
+            }
+            let (done, remaining) = instructions.split_at(step);
+            simulate(&CrateMover9001, &mut stacks, done)?;
+            println!("{}", serialize(&stacks, remaining));
+        }
+        if arg == "--reverse" {
+            let (mut stacks, instructions) = input.clone();
+            simulate(&CrateMover9001, &mut stacks, &instructions)?;
+            simulate_backwards(&CrateMover9001, &mut stacks, &instructions)?;
+            println!("Reconstructed:\n{stacks}");
+        }
+        if arg == "--traced" {
+            let (stacks, instructions) = &input;
+            for (part, mover) in [
+                (1, &CrateMover9000 as &dyn CrateMover),
+                (2, &CrateMover9001),
+            ] {
+                let tops = traced_tops(mover, stacks, instructions)?;
+                println!("Part {part} (traced): {}", tops.answer(placeholder));
+            }
+        }
+        if arg == "--contents" {
+            let (mut stacks, instructions) = input.clone();
+            simulate(&CrateMover9001, &mut stacks, &instructions)?;
+            for (index, height) in stacks.heights().into_iter().enumerate() {
+                let stack = stacks.stack(index).unwrap_or_default();
+                let crates: String = stack.iter().map(|c| c.0).collect();
+                println!("Stack {} ({height} crates): {crates}", index + 1);
+            }
+        }
+        if arg == "--provenance" {
+            let (stacks, instructions) = &input;
+            for (part, mover) in [
+                (1, &CrateMover9000 as &dyn CrateMover),
+                (2, &CrateMover9001),
+            ] {
+                let provenance = Provenance::new(mover, stacks, instructions)?;
+                println!("Part {part}:");
+                for index in 0..stacks.0.len() {
+                    let Some(top) = provenance.top(index) else {
+                        continue;
+                    };
+                    let last = match top.last_move() {
+                        Some(last) => format!("last by {}: {}", last, instructions[last]),
+                        None => "never".to_string(),
+                    };
+                    println!(
+                        "  [{}] on stack {} started on stack {} at height {}, moved {} times, {last}",
+                        top.item.0,
+                        index + 1,
+                        top.stack + 1,
+                        top.height,
+                        top.moves.len(),
+                    );
+                }
+                if let Some(busiest) = provenance.crates().iter().max_by_key(|h| h.moves.len()) {
+                    println!(
+                        "  [{}] moved the most, {} times",
+                        busiest.item.0,
+                        busiest.moves.len()
+                    );
+                }
+            }
+        }
+        if arg == "--repl" {
+            let (stacks, _) = input.clone();
+            repl::run(stacks, std::io::stdin().lock(), &mut std::io::stdout())?;
+        }
+    }
     Ok(())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use proptest::prelude::*;
     const INPUT: &str = include_str!("input_test.txt");
 
     #[test]
     fn test_part1() {
         let res = part1(parse(INPUT).unwrap()).unwrap();
-        assert_eq!(res, String::from("CMZ"))
+        assert_eq!(res.to_string(), "CMZ")
     }
 
     #[test]
     fn test_part2() {
         let res = part2(parse(INPUT).unwrap()).unwrap();
-        assert_eq!(res, String::from("MCD"))
+        assert_eq!(res.to_string(), "MCD")
+    }
+
+    #[test]
+    fn test_empty_stacks() {
+        let input = format!("{INPUT}\nmove 4 from 3 to 2\nmove 1 from 1 to 2");
+        let (mut stacks, instructions) = parse(&input).unwrap();
+        simulate(&CrateMover9001, &mut stacks, &instructions).unwrap();
+        let tops = stacks.tops();
+        assert_eq!(tops, Tops(vec![None, Some(Crate('M')), None]));
+        assert_eq!(tops.to_string(), " M ");
+        assert_eq!(tops.answer('-'), "-M-");
+        assert_eq!(stacks.heights(), vec![0, 6, 0]);
+        let crates: String = stacks.stack(1).unwrap().iter().map(|c| c.0).collect();
+        assert_eq!(crates, "CPZNDM");
+        assert_eq!(stacks.stack(3), None);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let (stacks, instructions) = parse(INPUT).unwrap();
+        assert_eq!(serialize(&stacks, &instructions), INPUT);
+    }
+
+    #[test]
+    fn test_ragged_drawing() {
+        let trimmed = INPUT.lines().map(str::trim_end).join("\n");
+        assert_eq!(parse(&trimmed).unwrap(), parse(INPUT).unwrap());
+        let (stacks, _) = parse(" 1   2 \n\n").unwrap();
+        assert_eq!(stacks, Stacks(vec![vec![], vec![]]));
+    }
+
+    #[test]
+    fn test_invalid_drawing() {
+        let error = |input: &str| parse(input).unwrap_err().to_string();
+        assert_eq!(
+            error("[A]\n    [B]\n 1   2\n"),
+            "line 1: crate [A] in stack 1 floats above a gap"
+        );
+        assert_eq!(
+            error("[A] [B]\n 1\n"),
+            "line 1: row is wider than the 1 labelled stacks"
+        );
+        assert_eq!(
+            error("[A] [B]\n 1   3\n"),
+            "line 2: expected stack label 2, found \"3\""
+        );
+        assert_eq!(
+            error("[A] [B]\n 1  2\n"),
+            "line 2: stack label 2 is not under its column"
+        );
+        assert_eq!(
+            error("[A] (B)\n 1   2\n"),
+            "line 1: expected a crate or gap in stack 2, found \"(B)\""
+        );
+        assert_eq!(
+            error("\nmove 1 from 1 to 2"),
+            "line 1: expected a drawing of the stacks"
+        );
+    }
+
+    fn input() -> impl Strategy<Value = Input> {
+        let stacks = prop::collection::vec(prop::collection::vec(b'A'..=b'Z', 0..6), 1..14);
+        stacks.prop_flat_map(|stacks| {
+            let count = stacks.len() as u32;
+            let instruction = (0..20u32, 0..count, 0..count)
+                .prop_map(|(amount, from, to)| Instruction { amount, from, to });
+            let stacks = Stacks(
+                stacks
+                    .into_iter()
+                    .map(|stack| stack.into_iter().map(|c| Crate(c as char)).collect())
+                    .collect(),
+            );
+            (Just(stacks), prop::collection::vec(instruction, 0..20))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn serialize_round_trip((stacks, instructions) in input()) {
+            let text = serialize(&stacks, &instructions);
+            prop_assert_eq!(parse(&text).unwrap(), (stacks, instructions));
+        }
+    }
+
+    #[test]
+    fn test_invalid_instruction() {
+        let input = INPUT.replace("move 2 from 2 to 1", "move 2 from 0 to 1");
+        let err = parse(&input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 8: invalid instruction \"move 2 from 0 to 1\""
+        );
+        let err = part1(parse(&INPUT.replace("move 3", "move 4")).unwrap()).unwrap_err();
+        let err = err.downcast::<crane::ExecutionError>().unwrap();
+        assert_eq!(err.index, 1);
+        assert_eq!(err.sizes, vec![3, 2, 1]);
     }
 }
@@ -0,0 +1,129 @@
+use crate::{
+    crane::{apply, CrateMover, ExecutionError},
+    Crate, Instruction, Stacks,
+};
+
+/// Where a crate started and which instructions moved it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History {
+    pub item: Crate,
+    /// Index of the stack the crate started on.
+    pub stack: usize,
+    /// How many crates were below it at the start.
+    pub height: usize,
+    /// Indices of the instructions that took it to another stack, in order.
+    pub moves: Vec<usize>,
+}
+
+impl History {
+    pub fn last_move(&self) -> Option<usize> {
+        self.moves.last().copied()
+    }
+}
+
+/// The history of every crate over a simulation, and where each one ended up.
+pub struct Provenance {
+    crates: Vec<History>,
+    /// Crates on each stack at the end, bottom first, as indices into `crates`.
+    stacks: Vec<Vec<usize>>,
+}
+
+impl Provenance {
+    /// Runs the instructions on labels standing in for the crates, noting
+    /// after each move the labels that landed on top of the target stack.
+    pub fn new(
+        mover: &dyn CrateMover,
+        stacks: &Stacks,
+        instructions: &[Instruction],
+    ) -> Result<Self, ExecutionError> {
+        let mut crates = Vec::new();
+        let mut labels = Vec::with_capacity(stacks.0.len());
+        for (stack, items) in stacks.0.iter().enumerate() {
+            let mut column = Vec::with_capacity(items.len());
+            for (height, item) in items.iter().enumerate() {
+                column.push(crates.len());
+                crates.push(History {
+                    item: *item,
+                    stack,
+                    height,
+                    moves: Vec::new(),
+                });
+            }
+            labels.push(column);
+        }
+
+        for (index, inst) in instructions.iter().enumerate() {
+            apply(&mut labels, index, inst, &mover.lifts(inst.amount as usize))?;
+            if inst.from == inst.to {
+                continue;
+            }
+            let target = &labels[inst.to as usize];
+            for label in &target[target.len() - inst.amount as usize..] {
+                crates[*label].moves.push(index);
+            }
+        }
+        Ok(Self {
+            crates,
+            stacks: labels,
+        })
+    }
+
+    /// Every crate, ordered by starting stack and then from the bottom up.
+    pub fn crates(&self) -> &[History] {
+        &self.crates
+    }
+
+    /// The crates left on stack `index`, bottom first.
+    pub fn stack(&self, index: usize) -> impl Iterator<Item = &History> {
+        self.stacks
+            .get(index)
+            .into_iter()
+            .flatten()
+            .map(|label| &self.crates[*label])
+    }
+
+    pub fn top(&self, index: usize) -> Option<&History> {
+        self.stack(index).last()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        crane::{CrateMover9000, CrateMover9001},
+        parse,
+    };
+    const INPUT: &str = include_str!("input_test.txt");
+
+    #[test]
+    fn test_provenance() {
+        let (stacks, instructions) = parse(INPUT).unwrap();
+        let provenance = Provenance::new(&CrateMover9000, &stacks, &instructions).unwrap();
+        let tops: Vec<_> = (0..3).map(|index| provenance.top(index).unwrap()).collect();
+        assert_eq!(
+            tops[0],
+            &History {
+                item: Crate('C'),
+                stack: 1,
+                height: 1,
+                moves: vec![2],
+            }
+        );
+        assert_eq!(tops[1].moves, vec![2, 3]);
+        assert_eq!(
+            (tops[2].item, tops[2].stack, tops[2].height),
+            (Crate('Z'), 0, 0)
+        );
+        assert_eq!(tops[2].last_move(), Some(1));
+        let moved: Vec<usize> = provenance.crates().iter().map(|h| h.moves.len()).collect();
+        assert_eq!(moved, vec![1, 1, 2, 1, 2, 0]);
+
+        let provenance = Provenance::new(&CrateMover9001, &stacks, &instructions).unwrap();
+        let order: String = provenance.stack(2).map(|h| h.item.0).collect();
+        assert_eq!(order, "PZND");
+        assert_eq!(provenance.top(1).unwrap().item, Crate('C'));
+        assert_eq!(provenance.top(1).unwrap().moves, vec![2, 3]);
+        assert_eq!(provenance.top(3), None);
+    }
+}
@@ -0,0 +1,94 @@
+use std::{fmt, io::Write, num::NonZeroUsize};
+
+use anyhow::Result;
+use itertools::Itertools;
+
+use crate::{
+    crane::{execute, CrateMover},
+    Instruction, Stacks,
+};
+
+impl fmt::Display for Stacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let height = self.0.iter().map(Vec::len).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let row = self.0.iter().map(|stack| match stack.get(level) {
+                Some(c) => format!("[{}]", c.0),
+                None => "   ".to_string(),
+            });
+            writeln!(f, "{}", row.format(" "))?;
+        }
+        // Each label starts under the crate letter of its column.
+        let labels: String = (1..=self.0.len())
+            .map(|label| format!(" {label:<3}"))
+            .collect();
+        let width = (4 * self.0.len()).saturating_sub(1);
+        write!(f, "{:<width$}", labels.trim_end())
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.amount,
+            self.from + 1,
+            self.to + 1
+        )
+    }
+}
+
+/// Runs the instructions, drawing the stacks before the first one and after
+/// every `every`th one as well as the last.
+pub fn simulate_steps(
+    mover: &dyn CrateMover,
+    stacks: &mut Stacks,
+    instructions: &[Instruction],
+    every: NonZeroUsize,
+    out: &mut impl Write,
+) -> Result<()> {
+    writeln!(out, "Initial state:\n{stacks}\n")?;
+    for (index, inst) in instructions.iter().enumerate() {
+        execute(mover, stacks, index, inst)?;
+        let step = index + 1;
+        if step.is_multiple_of(every.get()) || step == instructions.len() {
+            writeln!(out, "After step {step}, {inst}:\n{stacks}\n")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{crane::CrateMover9001, parse};
+    const INPUT: &str = include_str!("input_test.txt");
+
+    #[test]
+    fn test_render() {
+        let (stacks, _) = parse(INPUT).unwrap();
+        assert_eq!(stacks.to_string(), INPUT.split("\n\n").next().unwrap());
+    }
+
+    #[test]
+    fn test_simulate_steps() {
+        let (mut stacks, instructions) = parse(INPUT).unwrap();
+        let mut out = Vec::new();
+        simulate_steps(
+            &CrateMover9001,
+            &mut stacks,
+            &instructions,
+            NonZeroUsize::new(3).unwrap(),
+            &mut out,
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("Initial state:\n    [D]    \n"));
+        assert!(!out.contains("After step 1,"));
+        assert!(out.contains(
+            "After step 3, move 2 from 2 to 1:\n        [D]\n        [N]\n[C]     [Z]\n"
+        ));
+        assert!(out.ends_with("After step 4, move 1 from 1 to 2:\n        [D]\n        [N]\n        [Z]\n[M] [C] [P]\n 1   2   3 \n\n"));
+    }
+}
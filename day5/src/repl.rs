@@ -0,0 +1,156 @@
+use std::{
+    fs,
+    io::{BufRead, Write},
+};
+
+use anyhow::{anyhow, Result};
+use nom::combinator::all_consuming;
+
+use crate::{
+    crane::{CappedCrateMover, CrateMover, CrateMover9000, CrateMover9001},
+    parse, parse_instruction, serialize,
+    simulator::Simulator,
+    Stacks,
+};
+
+const HELP: &str = "\
+commands:
+  move N from A to B   move crates with the current crane
+  crane 9000|9001      switch to one-by-one or batch moves
+  crane capped N       switch to a crane lifting at most N crates
+  undo, redo           step back or forward through the moves
+  load PATH            replace the stacks with those of an input file
+  save PATH            write the stacks to a file in input format
+  show                 draw the stacks
+  help                 show this message
+  quit                 leave";
+
+fn crane(args: &[&str]) -> Result<Box<dyn CrateMover>> {
+    match args {
+        ["9000"] => Ok(Box::new(CrateMover9000)),
+        ["9001"] => Ok(Box::new(CrateMover9001)),
+        ["capped", capacity] => Ok(Box::new(CappedCrateMover::new(capacity.parse()?)?)),
+        _ => Err(anyhow!(
+            "expected `crane 9000`, `crane 9001` or `crane capped N`"
+        )),
+    }
+}
+
+/// Handles one command, returning whether the stacks should be drawn after it.
+fn command(simulator: &mut Simulator, line: &str, out: &mut impl Write) -> Result<bool> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        ["move", ..] => {
+            let (_, instruction) =
+                all_consuming(parse_instruction)(line).map_err(|_| anyhow!("invalid move"))?;
+            simulator.step(instruction)?;
+        }
+        ["crane", args @ ..] => simulator.set_mover(crane(args)?),
+        ["undo"] => {
+            let instruction = simulator.undo().ok_or(anyhow!("nothing to undo"))?;
+            writeln!(out, "undid {instruction}")?;
+        }
+        ["redo"] => {
+            let instruction = simulator.redo().ok_or(anyhow!("nothing to redo"))?;
+            writeln!(out, "redid {instruction}")?;
+        }
+        ["load", path] => {
+            let (stacks, _) = parse(&fs::read_to_string(path)?)?;
+            simulator.reset(stacks);
+        }
+        ["save", path] => {
+            fs::write(path, serialize(simulator.stacks(), &[]))?;
+            writeln!(out, "saved to {path}")?;
+            return Ok(false);
+        }
+        ["show"] => {}
+        ["help"] => {
+            writeln!(out, "{HELP}")?;
+            return Ok(false);
+        }
+        [] => return Ok(false),
+        _ => return Err(anyhow!("unknown command, try `help`")),
+    }
+    Ok(true)
+}
+
+/// Reads commands from `input` until it ends or `quit` is entered.
+pub fn run(stacks: Stacks, input: impl BufRead, out: &mut impl Write) -> Result<()> {
+    let mut simulator = Simulator::new(stacks, Box::new(CrateMover9000));
+    writeln!(out, "{}\n", simulator.stacks())?;
+    write!(out, "> ")?;
+    out.flush()?;
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line == "quit" {
+            break;
+        }
+        match command(&mut simulator, line, out) {
+            Ok(true) => writeln!(out, "{}\n", simulator.stacks())?,
+            Ok(false) => {}
+            Err(err) => writeln!(out, "error: {err}")?,
+        }
+        write!(out, "> ")?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    const INPUT: &str = include_str!("input_test.txt");
+
+    fn session(script: &str) -> String {
+        let (stacks, _) = parse(INPUT).unwrap();
+        let mut out = Vec::new();
+        run(stacks, script.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_session() {
+        static RUN: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "day5_repl_test_{}_{}.txt",
+            std::process::id(),
+            RUN.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = std::env::temp_dir().join(name);
+        let path = path.to_str().unwrap();
+        let out = session(&format!(
+            "crane 9001\nmove 2 from 2 to 1\nundo\nmove 3 from 2 to 3\nsave {path}\nquit\nshow\n"
+        ));
+        let saved = fs::read_to_string(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert!(out.contains("undid move 2 from 2 to 1\n"));
+        assert!(out.ends_with("> "));
+        assert_eq!(out.matches("> ").count(), 6);
+        let (stacks, instructions) = parse(&saved).unwrap();
+        assert!(instructions.is_empty());
+        assert_eq!(
+            stacks.to_string(),
+            "        [D]\n        [C]\n[N]     [M]\n[Z]     [P]\n 1   2   3 "
+        );
+    }
+
+    #[test]
+    fn test_session_errors() {
+        let out = session("move 1 from 4 to 1\nmove one\nundo\ncrane 9002\ncrane capped 0\nfly\n");
+        let errors: Vec<&str> = out.lines().filter(|line| line.contains("error")).collect();
+        assert_eq!(
+            errors,
+            [
+                "> error: instruction 0 (move 1 from 4 to 1) on stacks of sizes [2, 3, 1]: there is no stack 4",
+                "> error: invalid move",
+                "> error: nothing to undo",
+                "> error: expected `crane 9000`, `crane 9001` or `crane capped N`",
+                "> error: a crane has to lift at least one crate at a time",
+                "> error: unknown command, try `help`",
+            ]
+        );
+    }
+}
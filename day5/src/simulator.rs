@@ -0,0 +1,120 @@
+use crate::{
+    crane::{apply, revert, CrateMover, ExecutionError},
+    Instruction, Stacks,
+};
+
+/// An instruction as it was carried out, so it can be undone and redone even
+/// after the crane has been swapped.
+#[derive(Debug, Clone)]
+struct Step {
+    instruction: Instruction,
+    lifts: Vec<usize>,
+}
+
+pub struct Simulator {
+    stacks: Stacks,
+    mover: Box<dyn CrateMover>,
+    done: Vec<Step>,
+    undone: Vec<Step>,
+}
+
+impl Simulator {
+    pub fn new(stacks: Stacks, mover: Box<dyn CrateMover>) -> Self {
+        Self {
+            stacks,
+            mover,
+            done: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn stacks(&self) -> &Stacks {
+        &self.stacks
+    }
+
+    /// Replaces the stacks, forgetting every step taken so far.
+    pub fn reset(&mut self, stacks: Stacks) {
+        self.stacks = stacks;
+        self.done.clear();
+        self.undone.clear();
+    }
+
+    pub fn set_mover(&mut self, mover: Box<dyn CrateMover>) {
+        self.mover = mover;
+    }
+
+    /// Carries out `instruction`, dropping any steps left to redo.
+    pub fn step(&mut self, instruction: Instruction) -> Result<(), ExecutionError> {
+        let lifts = self.mover.lifts(instruction.amount as usize);
+        apply(&mut self.stacks.0, self.done.len(), &instruction, &lifts)?;
+        self.done.push(Step { instruction, lifts });
+        self.undone.clear();
+        Ok(())
+    }
+
+    /// Reverts the last step, returning its instruction.
+    pub fn undo(&mut self) -> Option<Instruction> {
+        let step = self.done.pop()?;
+        revert(
+            &mut self.stacks.0,
+            self.done.len(),
+            &step.instruction,
+            &step.lifts,
+        )
+        .expect("the stacks are as the step left them");
+        self.undone.push(step.clone());
+        Some(step.instruction)
+    }
+
+    /// Carries out the last undone step again with the crane that first ran it.
+    pub fn redo(&mut self) -> Option<Instruction> {
+        let step = self.undone.pop()?;
+        apply(
+            &mut self.stacks.0,
+            self.done.len(),
+            &step.instruction,
+            &step.lifts,
+        )
+        .expect("the stacks are as the undo left them");
+        self.done.push(step.clone());
+        Some(step.instruction)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        crane::{CrateMover9000, CrateMover9001},
+        parse,
+    };
+    const INPUT: &str = include_str!("input_test.txt");
+
+    #[test]
+    fn test_undo_redo() {
+        let (stacks, instructions) = parse(INPUT).unwrap();
+        let mut simulator = Simulator::new(stacks.clone(), Box::new(CrateMover9000));
+        for (index, inst) in instructions.iter().enumerate() {
+            if index == 2 {
+                simulator.set_mover(Box::new(CrateMover9001));
+            }
+            simulator.step(*inst).unwrap();
+        }
+        let end = simulator.stacks().clone();
+
+        assert_eq!(simulator.undo(), Some(instructions[3]));
+        assert_eq!(simulator.undo(), Some(instructions[2]));
+        assert_eq!(simulator.undo(), Some(instructions[1]));
+        assert_eq!(simulator.stacks().tops().to_string(), "DCP");
+        assert_eq!(simulator.undo(), Some(instructions[0]));
+        assert_eq!(simulator.undo(), None);
+        assert_eq!(simulator.stacks(), &stacks);
+
+        while simulator.redo().is_some() {}
+        assert_eq!(simulator.stacks(), &end);
+
+        simulator.undo();
+        simulator.step(instructions[3]).unwrap();
+        assert_eq!(simulator.redo(), None);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    crane::{check, CrateMover, ExecutionError},
+    Instruction, Stacks, Tops,
+};
+
+/// Finds the crate on top of each stack after the instructions without moving
+/// any crates. Only the stack heights are followed forwards, then each top is
+/// traced back through the instructions to its place in the starting stacks,
+/// so the work does not depend on how many crates are moved.
+pub fn traced_tops(
+    mover: &dyn CrateMover,
+    stacks: &Stacks,
+    instructions: &[Instruction],
+) -> Result<Tops, ExecutionError> {
+    let mut heights: Vec<usize> = stacks.0.iter().map(Vec::len).collect();
+    for (index, inst) in instructions.iter().enumerate() {
+        check(|stack| heights.get(stack as usize).copied(), inst).map_err(|source| {
+            ExecutionError {
+                index,
+                instruction: *inst,
+                sizes: heights.clone(),
+                source,
+            }
+        })?;
+        heights[inst.from as usize] -= inst.amount as usize;
+        heights[inst.to as usize] += inst.amount as usize;
+    }
+
+    // Positions are counted from the top of each stack.
+    let mut positions: Vec<(usize, usize)> = (0..stacks.0.len()).map(|stack| (stack, 0)).collect();
+    for inst in instructions.iter().rev() {
+        let (from, to, amount) = (inst.from as usize, inst.to as usize, inst.amount as usize);
+        if from == to {
+            continue;
+        }
+        for (stack, position) in positions.iter_mut() {
+            if *stack == to {
+                if *position < amount {
+                    *stack = from;
+                    *position = mover.source_position(amount, *position);
+                } else {
+                    *position -= amount;
+                }
+            } else if *stack == from {
+                *position += amount;
+            }
+        }
+    }
+
+    Ok(Tops(
+        positions
+            .into_iter()
+            .map(|(stack, position)| {
+                let stack = &stacks.0[stack];
+                let index = stack.len().checked_sub(position + 1)?;
+                Some(stack[index])
+            })
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::crane::{simulate, CappedCrateMover, CrateMover9000, CrateMover9001};
+    use crate::Crate;
+    use proptest::prelude::*;
+
+    /// Stacks with moves whose amounts are cut down to what the source stack holds.
+    fn valid_input() -> impl Strategy<Value = (Stacks, Vec<Instruction>)> {
+        (1..6usize)
+            .prop_flat_map(|count| {
+                let stacks = prop::collection::vec(prop::collection::vec(b'A'..=b'Z', 0..8), count);
+                let moves = prop::collection::vec((0..10usize, 0..count, 0..count), 0..40);
+                (stacks, moves)
+            })
+            .prop_map(|(stacks, moves)| {
+                let mut heights: Vec<usize> = stacks.iter().map(Vec::len).collect();
+                let instructions = moves
+                    .into_iter()
+                    .map(|(amount, from, to)| {
+                        let amount = amount.min(heights[from]);
+                        heights[from] -= amount;
+                        heights[to] += amount;
+                        Instruction {
+                            amount: amount as u32,
+                            from: from as u32,
+                            to: to as u32,
+                        }
+                    })
+                    .collect();
+                let stacks = stacks
+                    .into_iter()
+                    .map(|stack| stack.into_iter().map(|c| Crate(c as char)).collect())
+                    .collect();
+                (Stacks(stacks), instructions)
+            })
+    }
+
+    fn naive_tops(mover: &dyn CrateMover, stacks: &Stacks, instructions: &[Instruction]) -> Tops {
+        let mut stacks = stacks.clone();
+        simulate(mover, &mut stacks, instructions).unwrap();
+        stacks.tops()
+    }
+
+    #[test]
+    fn test_invalid() {
+        let (stacks, mut instructions) = crate::parse(include_str!("input_test.txt")).unwrap();
+        instructions[1].amount = 4;
+        let mut naive = stacks.clone();
+        let expected = simulate(&CrateMover9000, &mut naive, &instructions).unwrap_err();
+        let err = traced_tops(&CrateMover9000, &stacks, &instructions).unwrap_err();
+        assert_eq!(err, expected);
+    }
+
+    proptest! {
+        #[test]
+        fn matches_naive((stacks, instructions) in valid_input(), capacity in 1..5usize) {
+            let movers: [&dyn CrateMover; 3] =
+                [&CrateMover9000, &CrateMover9001, &CappedCrateMover::new(capacity).unwrap()];
+            for mover in movers {
+                prop_assert_eq!(
+                    traced_tops(mover, &stacks, &instructions).unwrap(),
+                    naive_tops(mover, &stacks, &instructions)
+                );
+            }
+        }
+    }
+}